@@ -0,0 +1,287 @@
+//! Audit trail for OK.KOMM calls.
+//!
+//! Register lookups (e.g. EWO Melderegisterauskunft) have to be logged for
+//! data protection audits (§ 40 BMG). A [`Client`](crate::Client) configured
+//! with an [`AuditSink`] hands one [`AuditRecord`] per call to the sink.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::redact;
+use crate::xml::{self, WriteXml};
+use crate::zkoxml::{AppsInfo, ZkocxmlInfo};
+use crate::OkKommAktion;
+
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord) -> anyhow::Result<()>;
+}
+
+/// How the SUCHE of a call ends up in the audit record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuditSuche {
+    /// SUCHE is not recorded at all.
+    Omit,
+    /// Only the hex encoded SHA-256 of the SUCHE XML is recorded.
+    #[default]
+    Sha256,
+    /// The SUCHE XML is recorded verbatim.
+    Content,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum AuditStatus {
+    Ok,
+    Fehler {
+        #[serde(rename = "fehler_typ")]
+        typ: String,
+        #[serde(rename = "fehler_text")]
+        text: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl AuditStatus {
+    pub(crate) fn from_decoded<T>(decoded: &anyhow::Result<(Option<ZkocxmlInfo>, T)>) -> Self {
+        match decoded {
+            Ok((info, _)) => match info.as_ref().and_then(|info| info.error()) {
+                Some(err) => AuditStatus::Fehler {
+                    typ: err.typ.to_owned(),
+                    text: err.text.to_owned(),
+                },
+                None => AuditStatus::Ok,
+            },
+            Err(err) => AuditStatus::Error {
+                message: format!("{err:#}"),
+            },
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Serialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    pub verfahren: String,
+    pub typ: String,
+    pub ausfuehrung: String,
+    pub ziel_ags: String,
+    pub kennung: Option<String>,
+    pub ip_adresse: Option<String>,
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suche_sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suche: Option<String>,
+    #[serde(flatten)]
+    pub status: AuditStatus,
+}
+
+impl std::fmt::Debug for AuditRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditRecord")
+            .field("timestamp", &self.timestamp)
+            .field("verfahren", &self.verfahren)
+            .field("typ", &self.typ)
+            .field("ausfuehrung", &self.ausfuehrung)
+            .field("ziel_ags", &self.ziel_ags)
+            .field("kennung", &redact::opt("APPS_KENNUNG", &self.kennung))
+            .field(
+                "ip_adresse",
+                &redact::opt("APPS_IP_ADRESSE", &self.ip_adresse),
+            )
+            .field("request_id", &self.request_id)
+            .field("suche_sha256", &self.suche_sha256)
+            .field("suche", &self.suche.as_deref().map(redact::Xml))
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Audit {
+    sink: Arc<dyn AuditSink>,
+    suche: AuditSuche,
+}
+
+impl Audit {
+    pub(crate) fn new(sink: Arc<dyn AuditSink>, suche: AuditSuche) -> Self {
+        Self { sink, suche }
+    }
+
+    pub(crate) fn begin<T>(
+        &self,
+        info: &OkKommAktion,
+        apps_info: Option<&AppsInfo>,
+        suche: &T,
+    ) -> AuditRecord
+    where
+        T: WriteXml,
+    {
+        let suche = match self.suche {
+            AuditSuche::Omit => None,
+            _ => match xml::to_bytes(suche) {
+                Ok(bytes) => Some(bytes),
+                Err(err) => {
+                    log::error!(
+                        "{}",
+                        redact::text(&format!(
+                            "Error while serializing SUCHE for audit record: {err:#?}"
+                        ))
+                    );
+                    None
+                }
+            },
+        };
+        let (suche_sha256, suche) = match (self.suche, suche) {
+            (AuditSuche::Sha256, Some(bytes)) => {
+                (Some(format!("{:x}", Sha256::digest(&bytes))), None)
+            }
+            (AuditSuche::Content, Some(bytes)) => {
+                (None, Some(String::from_utf8_lossy(&bytes).to_string()))
+            }
+            _ => (None, None),
+        };
+        AuditRecord {
+            timestamp: Utc::now(),
+            verfahren: info.verfahren.clone(),
+            typ: info.typ.clone(),
+            ausfuehrung: info.ausfuehrung.clone(),
+            ziel_ags: info.ziel_ags.clone(),
+            kennung: apps_info.and_then(|v| v.kennung.clone()),
+            ip_adresse: apps_info.and_then(|v| v.ip_adresse.clone()),
+            request_id: apps_info.and_then(|v| v.request_id.clone()),
+            suche_sha256,
+            suche,
+            status: AuditStatus::Ok,
+        }
+    }
+
+    pub(crate) fn finish(&self, mut record: AuditRecord, status: AuditStatus) {
+        record.status = status;
+        if let Err(err) = self.sink.record(&record) {
+            log::error!(
+                "{}",
+                redact::text(&format!("Error while writing audit record: {err:#?}"))
+            );
+        }
+    }
+}
+
+/// Appends every [`AuditRecord`] as a single JSON line to a file.
+pub struct JsonLinesFileSink {
+    file: Mutex<File>,
+}
+
+impl JsonLinesFileSink {
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for JsonLinesFileSink {
+    fn record(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::Error::msg("audit file lock poisoned"))?;
+        file.write_all(&line)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::TimeZone;
+
+    use super::{Audit, AuditRecord, AuditSink, AuditStatus, AuditSuche, JsonLinesFileSink};
+    use crate::config::{AppsInfoConfig, Defaults};
+    use crate::testing;
+    use crate::zkoxml::RawRequest;
+    use crate::OkKommAktion;
+
+    struct MemorySink(Arc<std::sync::Mutex<Vec<AuditRecord>>>);
+
+    impl AuditSink for MemorySink {
+        fn record(&self, record: &AuditRecord) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines_file_sink() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("okkomm-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let audit = Audit::new(
+            Arc::new(JsonLinesFileSink::open(&path)?),
+            AuditSuche::Sha256,
+        );
+        let info = OkKommAktion::new(
+            "EWO".to_owned(),
+            "WEBWAHLSCHEIN".to_owned(),
+            "ABRUFEN".to_owned(),
+            "09000011".to_owned(),
+        );
+        let mut record = audit.begin(&info, None, &RawRequest("test".to_owned()));
+        record.timestamp = chrono::Utc
+            .with_ymd_and_hms(2023, 1, 24, 11, 17, 37)
+            .unwrap();
+        audit.finish(
+            record,
+            AuditStatus::Fehler {
+                typ: "F".to_owned(),
+                text: "nicht gefunden".to_owned(),
+            },
+        );
+        let content = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(
+            content,
+            "{\"timestamp\":\"2023-01-24T11:17:37Z\",\"verfahren\":\"EWO\",\"typ\":\"WEBWAHLSCHEIN\",\"ausfuehrung\":\"ABRUFEN\",\"ziel_ags\":\"09000011\",\"kennung\":null,\"ip_adresse\":null,\"request_id\":null,\"suche_sha256\":\"9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08\",\"result\":\"fehler\",\"fehler_typ\":\"F\",\"fehler_text\":\"nicht gefunden\"}\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_audit_configured_apps_info() -> anyhow::Result<()> {
+        let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut client = testing::client(testing::zkocxml("", ""), |request| {
+            assert!(request.contains("<APPS_KENNUNG>sachbearbeiter-7</APPS_KENNUNG>"));
+        })
+        .with_audit_sink(MemorySink(records.clone()), AuditSuche::Omit);
+        client.defaults = Defaults {
+            login: None,
+            apps_info: Some(AppsInfoConfig {
+                kennung: Some("sachbearbeiter-7".to_owned()),
+                ..AppsInfoConfig::default()
+            }),
+        };
+        let info = OkKommAktion::new(
+            "EWO".to_owned(),
+            "AUSKUNFT".to_owned(),
+            "ABRUFEN".to_owned(),
+            "09162000".to_owned(),
+        );
+        client
+            .call(info, RawRequest(String::new()), (), None)
+            .await?;
+        let records = records.lock().unwrap();
+        assert_eq!(records[0].kennung.as_deref(), Some("sachbearbeiter-7"));
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+//! `okkomm build`: prints the envelope `okkomm call` would send, without
+//! sending it.
+
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use anyhow::Context;
+use chrono::{NaiveDateTime, TimeZone};
+use chrono_tz::Europe::Berlin;
+
+use okkomm_rs::okkomm::{OkKommCallApplicationByte, OkKommCallApplicationByteRequest};
+use okkomm_rs::soap::{SoapRequest, SoapResponse};
+use okkomm_rs::transport::MemoryTransport;
+use okkomm_rs::zkoxml::AppsInfo;
+use okkomm_rs::Client;
+
+use crate::{message, pretty_xml, redact_password};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Indents ZKOCXML and SOAP envelope.
+    #[arg(long)]
+    pretty: bool,
+    /// APPS_DATUM and APPS_UHRZEIT in Europe/Berlin instead of now, e.g.
+    /// `2025-01-24T12:17:37`.
+    #[arg(long, value_parser = NaiveDateTime::from_str)]
+    timestamp: Option<NaiveDateTime>,
+    /// Prints AKT_TECHPWD instead of masking it.
+    #[arg(long)]
+    show_password: bool,
+    #[command(flatten)]
+    login: message::Login,
+    #[command(flatten)]
+    message: message::Message,
+}
+
+/// The outgoing ZKOCXML and SOAP envelope for `args`.
+fn build(args: Args) -> anyhow::Result<(String, String)> {
+    let apps_info = match args.timestamp {
+        Some(timestamp) => Some(AppsInfo::default_at(
+            &Berlin
+                .from_local_datetime(&timestamp)
+                .earliest()
+                .context("--timestamp does not exist in Europe/Berlin")?,
+        )),
+        None => None,
+    };
+    let (aktion, body) = args.message.into_message()?;
+    let client = args
+        .login
+        .apply(Client::from_transport(MemoryTransport::new(|_| {
+            anyhow::bail!("okkomm build does not send")
+        })))?;
+    let soap = client
+        .soap_body(aktion, body, (), apps_info)?
+        .to_message()?;
+    let soap = String::from_utf8(soap.to_vec())?;
+    // decoded from the envelope so that exactly the sent document is shown
+    let zkocxml = SoapResponse::<OkKommCallApplicationByteRequest>::from_str(&soap)?
+        .into_inner()
+        .context("no callApplicationByte in envelope")?
+        .zkocxml()?
+        .context("no xmlParameter in envelope")?;
+    if args.show_password {
+        return Ok((zkocxml, soap));
+    }
+    let redacted = redact_password(&zkocxml);
+    if redacted == zkocxml {
+        return Ok((zkocxml, soap));
+    }
+    let soap =
+        SoapRequest::new(OkKommCallApplicationByte::new(redacted.as_bytes())).to_message()?;
+    Ok((redacted, String::from_utf8(soap.to_vec())?))
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let pretty = args.pretty;
+    let (zkocxml, soap) = build(args)?;
+    if pretty {
+        println!("{}\n\n{}", pretty_xml(&zkocxml)?, pretty_xml(&soap)?);
+    } else {
+        println!("{zkocxml}\n\n{soap}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use clap::Parser;
+    use okkomm_rs::okkomm::OkKommCallApplicationByteRequest;
+    use okkomm_rs::soap::SoapResponse;
+
+    use super::build;
+    use crate::{Cli, Command};
+
+    fn build_args(args: &[&str]) -> anyhow::Result<super::Args> {
+        let cli = Cli::try_parse_from(["okkomm", "build"].iter().chain(args))?;
+        let Command::Build(args) = cli.command else {
+            panic!("not parsed as build");
+        };
+        Ok(*args)
+    }
+
+    fn sent_zkocxml(soap: &str) -> anyhow::Result<String> {
+        Ok(
+            SoapResponse::<OkKommCallApplicationByteRequest>::from_str(soap)?
+                .into_inner()
+                .unwrap()
+                .zkocxml()?
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_build() -> anyhow::Result<()> {
+        let suche = std::env::temp_dir().join(format!("okkomm-build-{}.xml", std::process::id()));
+        std::fs::write(&suche, "<MANDANTENANFRAGE/>\n")?;
+        let techpwd = std::env::temp_dir().join(format!("okkomm-build-pwd-{}", std::process::id()));
+        std::fs::write(&techpwd, "geheim\n")?;
+        let message = [
+            "--verfahren",
+            "EWO",
+            "--typ",
+            "MANDANTENANFRAGE",
+            "--ausfuehrung",
+            "ABRUFEN",
+            "--ziel-ags",
+            "",
+            suche.to_str().unwrap(),
+        ];
+        let (zkocxml, soap) = build(build_args(
+            &[&["--timestamp", "2025-01-24T12:17:37"][..], &message].concat(),
+        )?)?;
+        assert!(zkocxml
+            .contains("<APPS_DATUM>24.01.2025</APPS_DATUM><APPS_UHRZEIT>12:17:37</APPS_UHRZEIT>"));
+        assert!(zkocxml.contains("<SUCHE><MANDANTENANFRAGE/></SUCHE>"));
+        assert!(zkocxml.contains("<AKT_TECHUSER></AKT_TECHUSER><AKT_TECHPWD></AKT_TECHPWD>"));
+        assert!(soap.contains("<okk:xmlParameter"));
+
+        let login = [
+            "--techuser",
+            "portal",
+            "--techpwd-file",
+            techpwd.to_str().unwrap(),
+        ];
+        let (zkocxml, soap) = build(build_args(&[&login[..], &message].concat())?)?;
+        let masked = "<AKT_TECHUSER>portal</AKT_TECHUSER><AKT_TECHPWD>***</AKT_TECHPWD>";
+        assert!(zkocxml.contains(masked));
+        assert!(sent_zkocxml(&soap)?.contains(masked));
+
+        let (zkocxml, soap) = build(build_args(
+            &[&["--show-password"][..], &login, &message].concat(),
+        )?)?;
+        let sent = "<AKT_TECHUSER>portal</AKT_TECHUSER><AKT_TECHPWD>geheim</AKT_TECHPWD>";
+        assert!(zkocxml.contains(sent));
+        assert!(sent_zkocxml(&soap)?.contains(sent));
+
+        std::fs::remove_file(&suche)?;
+        std::fs::remove_file(&techpwd)?;
+        Ok(())
+    }
+}
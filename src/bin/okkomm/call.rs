@@ -0,0 +1,75 @@
+//! `okkomm call`: sends a SUCHE read from a file or stdin.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use okkomm_rs::response::OkKommResponse;
+use okkomm_rs::tls::ClientIdentity;
+use okkomm_rs::xml;
+use okkomm_rs::Client;
+
+use crate::message::{self, read};
+use crate::EXIT_FEHLER;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// URL of the OK.KOMM KomService.
+    #[arg(long, env = "OKKOMM_URL")]
+    url: String,
+    /// PEM root certificate to trust, may be repeated.
+    #[arg(long)]
+    ca_cert: Vec<PathBuf>,
+    /// PEM client certificate for mutual TLS.
+    #[arg(long, requires = "client_key")]
+    client_cert: Option<PathBuf>,
+    /// PEM private key of `--client-cert`.
+    #[arg(long, requires = "client_cert")]
+    client_key: Option<PathBuf>,
+    #[command(flatten)]
+    login: message::Login,
+    #[command(flatten)]
+    message: message::Message,
+}
+
+fn client(args: &Args) -> anyhow::Result<Client> {
+    let roots = match args.ca_cert.as_slice() {
+        [] => None,
+        paths => Some(paths.iter().map(|p| read(p)).collect::<Result<_, _>>()?),
+    };
+    let identity = match (&args.client_cert, &args.client_key) {
+        (Some(cert), Some(key)) => Some(ClientIdentity::new(read(cert)?, read(key)?)),
+        _ => None,
+    };
+    args.login.apply(Client::new_with_identity(
+        args.url.clone(),
+        roots,
+        identity,
+    )?)
+}
+
+pub async fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let client = client(&args)?;
+    let (aktion, body) = args.message.into_message()?;
+    let response = client.call(aktion, body, (), None).await?;
+    print(&response)
+}
+
+fn print(response: &OkKommResponse) -> anyhow::Result<ExitCode> {
+    let antwort = response
+        .info
+        .as_ref()
+        .and_then(|info| info.xml_system.system.antwort.as_ref());
+    if let Some(antwort) = antwort {
+        println!("{}", String::from_utf8_lossy(&xml::to_bytes(antwort)?));
+    }
+    if let Some(daten) = response.daten.as_deref() {
+        println!("{daten}");
+    }
+    match response.error() {
+        Some(fehler) => {
+            eprintln!("FEHLER {}: {}", fehler.typ, fehler.text);
+            Ok(ExitCode::from(EXIT_FEHLER))
+        }
+        None => Ok(ExitCode::SUCCESS),
+    }
+}
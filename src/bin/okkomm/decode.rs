@@ -0,0 +1,185 @@
+//! `okkomm decode`: unwraps captured `callApplicationByte` requests,
+//! `callApplicationByteResponse` answers or bare base64 ZKOCXML.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use okkomm_rs::okkomm::{OkKommCallApplicationByteRequest, OkKommCallApplicationByteResponse};
+use okkomm_rs::soap::SoapResponse;
+use okkomm_rs::zkoxml::{ContentContainerResponse, ZkocxmlInfo};
+
+use crate::{pretty_xml, read_input, redact_password, EXIT_FEHLER};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Writes content-container attachments to this directory.
+    #[arg(long, value_name = "DIR")]
+    attachments: Option<PathBuf>,
+    /// Captured SOAP envelope or base64 blob, stdin if absent or `-`.
+    file: Option<PathBuf>,
+}
+
+/// The ZKOCXML document inside `input`, which may be a SOAP envelope, a
+/// base64 blob or the ZKOCXML itself.
+fn zkocxml(input: &str) -> anyhow::Result<String> {
+    let input = input.trim();
+    if !input.starts_with('<') {
+        let blob: String = input.split_whitespace().collect();
+        let decoded = STANDARD
+            .decode(blob)
+            .context("input is neither XML nor base64")?;
+        return String::from_utf8(decoded).context("decoded ZKOCXML is not valid UTF-8");
+    }
+    if let Some(xml) = SoapResponse::<OkKommCallApplicationByteResponse>::from_str(input)
+        .ok()
+        .and_then(SoapResponse::into_inner)
+        .map(|body| body.zkocxml())
+        .transpose()?
+        .flatten()
+    {
+        return Ok(xml);
+    }
+    if let Some(xml) = SoapResponse::<OkKommCallApplicationByteRequest>::from_str(input)
+        .ok()
+        .and_then(SoapResponse::into_inner)
+        .map(|body| body.zkocxml())
+        .transpose()?
+        .flatten()
+    {
+        return Ok(xml);
+    }
+    if input.contains("<ZKOCXML") {
+        return Ok(input.to_owned());
+    }
+    anyhow::bail!("no xmlParameter or callApplicationByteReturn found")
+}
+
+fn field(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("")
+}
+
+fn summary(info: &ZkocxmlInfo) -> String {
+    let system = &info.xml_system.system;
+    let mut lines = Vec::new();
+    if let Some(aktion) = system.aktion.as_ref() {
+        lines.push(format!(
+            "AKTION   {} / {} / {} -> {}",
+            field(&aktion.verfahren),
+            field(&aktion.typ),
+            field(&aktion.ausfuehrung),
+            field(&aktion.ziel_ags),
+        ));
+    }
+    if let Some(login) = system.akt_login.as_ref() {
+        lines.push(format!("LOGIN    {}", field(&login.techuser)));
+    }
+    if let Some(apps) = system.apps_info.as_ref() {
+        lines.push(format!(
+            "APPS     {} {} ({})",
+            field(&apps.name),
+            field(&apps.version),
+            field(&apps.ags),
+        ));
+    }
+    if let Some(antwort) = system.antwort.as_ref() {
+        lines.push(format!(
+            "ANTWORT  {} {} {}",
+            field(&antwort.typ),
+            field(&antwort.datum),
+            field(&antwort.uhrzeit),
+        ));
+    }
+    if let Some(fehler) = info.error() {
+        lines.push(format!(
+            "FEHLER   {}: {} (FELD {}, WERT {})",
+            fehler.typ, fehler.text, fehler.feld, fehler.wert
+        ));
+    }
+    lines
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes every attachment to `dir`, named after its REF_ID.
+fn extract(container: &ContentContainerResponse, dir: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    for (i, attachment) in container.attachments.iter().enumerate() {
+        // REF_ID comes from the sender, never let it leave `dir`
+        let name = Path::new(&attachment.ref_id)
+            .file_name()
+            .map(|name| name.to_owned())
+            .unwrap_or_else(|| format!("attachment-{}", i + 1).into());
+        let path = dir.join(name);
+        std::fs::write(&path, &attachment.content)
+            .with_context(|| format!("cannot write {}", path.display()))?;
+        eprintln!(
+            "wrote {} ({}, {} bytes)",
+            path.display(),
+            attachment.content_type,
+            attachment.content.len()
+        );
+    }
+    Ok(())
+}
+
+pub fn run(args: Args) -> anyhow::Result<ExitCode> {
+    let input =
+        String::from_utf8(read_input(args.file.as_deref())?).context("input is not valid UTF-8")?;
+    let xml = zkocxml(&input)?;
+    let info = quick_xml::de::from_str::<ZkocxmlInfo>(&xml).context("no ZKOCXML SYSTEM")?;
+    println!("{}\n", summary(&info));
+    println!("{}", pretty_xml(&redact_password(&xml))?);
+
+    if let Some(container) = ContentContainerResponse::parse(&xml)? {
+        for message in &container.messages {
+            println!(
+                "\nMESSAGE {} ({})\n{}",
+                message.ref_id,
+                message.content_type,
+                pretty_xml(&message.content).unwrap_or_else(|_| message.content.clone())
+            );
+        }
+        match args.attachments.as_deref() {
+            Some(dir) => extract(&container, dir)?,
+            None => container.attachments.iter().for_each(|attachment| {
+                eprintln!(
+                    "attachment {} ({}, {} bytes), use --attachments DIR to extract",
+                    attachment.ref_id,
+                    attachment.content_type,
+                    attachment.content.len()
+                )
+            }),
+        }
+    }
+
+    Ok(match info.error() {
+        Some(_) => ExitCode::from(EXIT_FEHLER),
+        None => ExitCode::SUCCESS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    use okkomm_rs::okkomm::response_envelope;
+
+    use super::zkocxml;
+
+    #[test]
+    fn test_zkocxml() -> anyhow::Result<()> {
+        let xml = "<ZKOCXML><XML_SYSTEM><SYSTEM/></XML_SYSTEM></ZKOCXML>";
+        assert_eq!(zkocxml(&response_envelope(xml.as_bytes()))?, xml);
+        let blob = STANDARD.encode(xml);
+        assert_eq!(zkocxml(&format!("{}\n{}", &blob[..10], &blob[10..]))?, xml);
+        assert_eq!(zkocxml(xml)?, xml);
+        assert!(zkocxml("<foo/>").is_err());
+        Ok(())
+    }
+}
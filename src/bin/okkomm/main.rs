@@ -0,0 +1,112 @@
+//! `okkomm`: ad-hoc OK.KOMM calls for reproducing and debugging problems
+//! reported by municipalities.
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+mod build;
+mod call;
+mod decode;
+mod message;
+
+#[derive(Parser)]
+#[command(name = "okkomm", version, about = "OK.KOMM client for ad-hoc calls")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Sends a SUCHE to OK.KOMM and prints the decoded ANTWORT and DATEN.
+    Call(Box<call::Args>),
+    /// Decodes a captured SOAP envelope or base64 ZKOCXML offline.
+    Decode(decode::Args),
+    /// Prints the ZKOCXML and SOAP envelope `call` would send.
+    Build(Box<build::Args>),
+}
+
+/// Exit code of an answer carrying a FEHLER, as opposed to `1` for errors
+/// before an answer was decoded.
+pub const EXIT_FEHLER: u8 = 2;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Call(args) => call::run(*args).await,
+        Command::Decode(args) => decode::run(args),
+        Command::Build(args) => build::run(*args),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reads `path`, or stdin if it is absent or `-`.
+pub fn read_input(path: Option<&std::path::Path>) -> anyhow::Result<Vec<u8>> {
+    use anyhow::Context;
+    use std::io::Read;
+
+    match path {
+        Some(path) if path.as_os_str() != "-" => {
+            std::fs::read(path).with_context(|| format!("cannot read {}", path.display()))
+        }
+        _ => {
+            let mut input = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut input)
+                .context("cannot read stdin")?;
+            Ok(input)
+        }
+    }
+}
+
+/// Indents `xml` by two spaces per level.
+pub fn pretty_xml(xml: &str) -> anyhow::Result<String> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), b' ', 2);
+    // a start tag is held back so that `<A></A>` can be written as `<A/>`
+    let mut start = None;
+    loop {
+        match (reader.read_event()?, start.take()) {
+            (Event::Eof, _) => break,
+            (Event::End(_), Some(pending)) => writer.write_event(Event::Empty(pending))?,
+            (Event::Start(next), pending) => {
+                if let Some(pending) = pending {
+                    writer.write_event(Event::Start(pending))?;
+                }
+                start = Some(next);
+            }
+            (event, pending) => {
+                if let Some(pending) = pending {
+                    writer.write_event(Event::Start(pending))?;
+                }
+                writer.write_event(event)?;
+            }
+        }
+    }
+    Ok(String::from_utf8(writer.into_inner())?)
+}
+
+/// Masks a non-empty technical password (`AKT_TECHPWD`) in `xml`.
+pub fn redact_password(xml: &str) -> String {
+    let has_password = quick_xml::de::from_str::<okkomm_rs::zkoxml::ZkocxmlInfo>(xml)
+        .ok()
+        .and_then(|info| info.xml_system.system.akt_login)
+        .and_then(|login| login.techpwd)
+        .is_some_and(|techpwd| !techpwd.is_empty());
+    if !has_password {
+        return xml.to_owned();
+    }
+    okkomm_rs::redact::Redactor::new(["AKT_TECHPWD"])
+        .redact_xml(xml)
+        .into_owned()
+}
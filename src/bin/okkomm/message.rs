@@ -0,0 +1,170 @@
+//! AKTION, SUCHE and login arguments shared by `okkomm call` and
+//! `okkomm build`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::ValueEnum;
+
+use okkomm_rs::config::Credentials;
+use okkomm_rs::xml::{WriteXml, XmlWriter};
+use okkomm_rs::zkoxml::{
+    ContentContainer, ContentContainerAttachment, ContentContainerMessage, Error, RawBase64,
+    RawRequest,
+};
+use okkomm_rs::{Client, OkKommAktion};
+
+use crate::read_input;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    /// SUCHE is embedded as is.
+    Raw,
+    /// SUCHE is sent base64 encoded in `OK_KOMM_RAW_BASE64`.
+    Base64,
+    /// SUCHE is sent as message of an `OK_KOMM_CONTENTCONTAINER`.
+    ContentContainer,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Message {
+    #[arg(long)]
+    verfahren: String,
+    #[arg(long)]
+    typ: String,
+    #[arg(long)]
+    ausfuehrung: String,
+    #[arg(long)]
+    ziel_ags: String,
+    #[arg(long, value_enum, default_value_t = Mode::Raw)]
+    mode: Mode,
+    /// Attachment as `FILE[:CONTENT_TYPE]`, content-container mode only.
+    #[arg(long = "attach", value_name = "FILE[:CONTENT_TYPE]")]
+    attachments: Vec<String>,
+    /// REF_ID of the content-container message.
+    #[arg(long, default_value = "request")]
+    ref_id: String,
+    /// File holding the SUCHE XML, stdin if absent or `-`.
+    suche: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Login {
+    /// AKT_TECHUSER of every request.
+    #[arg(long, env = "OKKOMM_TECHUSER", requires = "techpwd_file")]
+    techuser: Option<String>,
+    /// File holding AKT_TECHPWD, trailing whitespace is ignored.
+    #[arg(long, env = "OKKOMM_TECHPWD_FILE", requires = "techuser")]
+    techpwd_file: Option<PathBuf>,
+}
+
+impl Login {
+    /// `client` sending the login, if one is given.
+    pub fn apply(&self, client: Client) -> anyhow::Result<Client> {
+        let Some(techuser) = self.techuser.clone() else {
+            return Ok(client);
+        };
+        let credentials = Credentials {
+            techuser,
+            techpwd: None,
+            techpwd_file: self.techpwd_file.clone(),
+        };
+        Ok(client.with_credentials(&credentials)?)
+    }
+}
+
+/// SUCHE as sent in the chosen [`Mode`].
+pub enum Body {
+    Raw(RawRequest),
+    Base64(RawBase64),
+    ContentContainer(
+        Vec<ContentContainerMessage>,
+        Vec<ContentContainerAttachment>,
+    ),
+}
+
+impl WriteXml for Body {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        match self {
+            Body::Raw(raw) => raw.write_xml(w),
+            Body::Base64(raw) => raw.write_xml(w),
+            Body::ContentContainer(messages, attachments) => ContentContainer {
+                messages,
+                attachments,
+            }
+            .write_xml(w),
+        }
+    }
+}
+
+pub fn read(path: &Path) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("cannot read {}", path.display()))
+}
+
+/// Splits `FILE[:CONTENT_TYPE]`; the content type defaults to
+/// `application/octet-stream`.
+fn parse_attachment(spec: &str) -> (&str, &str) {
+    match spec.rsplit_once(':') {
+        Some((file, content_type)) if content_type.contains('/') => (file, content_type),
+        _ => (spec, "application/octet-stream"),
+    }
+}
+
+fn attachment(spec: &str) -> anyhow::Result<ContentContainerAttachment> {
+    let (file, content_type) = parse_attachment(spec);
+    let path = Path::new(file);
+    Ok(ContentContainerAttachment {
+        content_type: content_type.to_owned(),
+        ref_id: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file.to_owned()),
+        content: read(path)?.into(),
+    })
+}
+
+impl Message {
+    /// Reads the SUCHE and attachments.
+    pub fn into_message(self) -> anyhow::Result<(OkKommAktion, Body)> {
+        if !self.attachments.is_empty() && self.mode != Mode::ContentContainer {
+            anyhow::bail!("--attach requires --mode content-container");
+        }
+        let suche = String::from_utf8(read_input(self.suche.as_deref())?)
+            .context("SUCHE is not valid UTF-8")?;
+        let suche = suche.trim().to_owned();
+        let body = match self.mode {
+            Mode::Raw => Body::Raw(RawRequest(suche)),
+            Mode::Base64 => Body::Base64(RawBase64 { body: suche }),
+            Mode::ContentContainer => Body::ContentContainer(
+                vec![ContentContainerMessage {
+                    content_type: "text/xml".to_owned(),
+                    ref_id: self.ref_id,
+                    content: suche,
+                }],
+                self.attachments
+                    .iter()
+                    .map(|spec| attachment(spec))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+        };
+        let aktion = OkKommAktion::new(self.verfahren, self.typ, self.ausfuehrung, self.ziel_ags);
+        Ok((aktion, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_attachment;
+
+    #[test]
+    fn test_parse_attachment() {
+        assert_eq!(
+            parse_attachment("scan.pdf:application/pdf"),
+            ("scan.pdf", "application/pdf")
+        );
+        assert_eq!(
+            parse_attachment("C:\\scan.bin"),
+            ("C:\\scan.bin", "application/octet-stream")
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Synchronous OK.KOMM client on top of `reqwest::blocking`.
+//!
+//! Mirrors the `send_*` API of [`crate::Client`] for programs that do not run
+//! an async runtime. Must not be used from within an async context.
+//! [`crate::transport::MemoryTransport`] works with both clients.
+
+use std::sync::Arc;
+
+use anyhow::Error;
+use bytes::Bytes;
+use serde::Deserialize;
+
+use crate::audit::{Audit, AuditRecord, AuditSink, AuditSuche};
+use crate::config::{ClientConfig, Defaults};
+use crate::okkomm::OkKommCallApplicationByte;
+use crate::response::OkKommResponse;
+use crate::soap::SoapRequest;
+use crate::tls::{self, ClientIdentity};
+use crate::transport::{default_headers, TransportResponse};
+use crate::xml::WriteXml;
+use crate::zkoxml::{AppsInfo, ContentContainerAttachment};
+use crate::{
+    decode_transport_response, finish_audit, handle_decoded, redact, soap_body, soap_body_base64,
+    soap_body_content_container, OkKommAktion,
+};
+
+/// Synchronous counterpart of [`crate::transport::Transport`].
+pub trait Transport: Send + Sync {
+    fn send(&self, body: Bytes) -> anyhow::Result<TransportResponse>;
+}
+
+/// Posts SOAP envelopes to a single OK.KOMM endpoint with `reqwest::blocking`.
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+    pub url: String,
+}
+
+/// Blocking counterpart of [`crate::transport::client_builder`].
+pub(crate) fn client_builder(
+    tls_root_certificates: Option<Vec<Vec<u8>>>,
+    identity: Option<ClientIdentity>,
+) -> anyhow::Result<reqwest::blocking::ClientBuilder> {
+    let mut client_builder = reqwest::blocking::ClientBuilder::new()
+        .no_proxy()
+        .default_headers(default_headers());
+
+    #[cfg(feature = "rustls-tls")]
+    {
+        client_builder = client_builder.use_rustls_tls();
+    }
+
+    // without a TLS backend only the `compile_error!` in `tls` is reported
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    {
+        for cert in tls::root_certificates(tls_root_certificates) {
+            client_builder = client_builder.add_root_certificate(cert);
+        }
+
+        if let Some(identity) = identity {
+            client_builder = client_builder.identity(identity.to_reqwest()?);
+        }
+    }
+
+    Ok(client_builder)
+}
+
+impl ReqwestTransport {
+    pub fn new(
+        url: String,
+        tls_root_certificates: Option<Vec<Vec<u8>>>,
+        identity: Option<ClientIdentity>,
+    ) -> anyhow::Result<Self> {
+        let client = client_builder(tls_root_certificates, identity)?.build()?;
+        Ok(Self::from_client(client, url))
+    }
+
+    pub fn from_client(client: reqwest::blocking::Client, url: String) -> Self {
+        Self { client, url }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, body: Bytes) -> anyhow::Result<TransportResponse> {
+        let response = self
+            .client
+            .post(&self.url)
+            .body(body)
+            .send()
+            .map_err(|err| {
+                Error::msg(redact::text(&format!(
+                    "Error while sending SOAP request to OK.KOMM: {err:#?}"
+                )))
+            })?;
+        let status = response.status().as_u16();
+        let body = response.bytes().map_err(|err| {
+            Error::msg(redact::text(&format!(
+                "Error while receiving SOAP response from OK.KOMM: {err:#?}"
+            )))
+        })?;
+        Ok(TransportResponse { status, body })
+    }
+}
+
+#[derive(Clone)]
+pub struct Client {
+    transport: Arc<dyn Transport>,
+    audit: Option<Audit>,
+    defaults: Defaults,
+}
+
+impl Client {
+    pub fn new(url: String, tls_root_certificates: Option<Vec<Vec<u8>>>) -> anyhow::Result<Self> {
+        Self::new_with_identity(url, tls_root_certificates, None)
+    }
+
+    pub fn new_with_identity(
+        url: String,
+        tls_root_certificates: Option<Vec<Vec<u8>>>,
+        identity: Option<ClientIdentity>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self::from_transport(ReqwestTransport::new(
+            url,
+            tls_root_certificates,
+            identity,
+        )?))
+    }
+
+    pub fn from_transport(transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            audit: None,
+            defaults: Defaults::default(),
+        }
+    }
+
+    /// Client for the endpoint, credentials and APPS_INFO of `config`. The
+    /// limits, circuit breaker and response cache only apply to the async
+    /// [`crate::Client`].
+    pub fn from_config(config: &ClientConfig) -> anyhow::Result<Self> {
+        let mut client = Self::from_transport(config.blocking_transport()?);
+        client.defaults = config.defaults()?;
+        Ok(client)
+    }
+
+    /// Hands an [`AuditRecord`] for every `send_*` call to `sink`.
+    pub fn with_audit_sink(mut self, sink: impl AuditSink + 'static, suche: AuditSuche) -> Self {
+        self.audit = Some(Audit::new(Arc::new(sink), suche));
+        self
+    }
+
+    pub fn transport(&self) -> &Arc<dyn Transport> {
+        &self.transport
+    }
+
+    pub fn soap_body<R, D>(
+        &self,
+        info: OkKommAktion,
+        request: impl Into<Option<R>>,
+        data: impl Into<Option<D>>,
+        apps_info: Option<AppsInfo>,
+    ) -> Result<SoapRequest<OkKommCallApplicationByte<bytes::Bytes>>, quick_xml::Error>
+    where
+        R: WriteXml,
+        D: WriteXml,
+    {
+        soap_body(&self.defaults, info, request, data, apps_info)
+    }
+
+    pub fn send_soap<T>(&self, soap_request: SoapRequest<T>) -> anyhow::Result<TransportResponse>
+    where
+        T: WriteXml,
+    {
+        let body = soap_request.to_message()?;
+        self.transport.send(body)
+    }
+
+    pub fn send_request<T>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<TransportResponse>
+    where
+        T: WriteXml,
+    {
+        let soap_request = self.soap_body(info, body, (), apps_info)?;
+        self.send_soap(soap_request)
+    }
+
+    fn begin_audit<T>(
+        &self,
+        info: &OkKommAktion,
+        apps_info: Option<&AppsInfo>,
+        body: &T,
+    ) -> Option<AuditRecord>
+    where
+        T: WriteXml,
+    {
+        self.audit
+            .as_ref()
+            .map(|audit| audit.begin(info, apps_info, body))
+    }
+
+    /// Sends `request` as SUCHE and `data` as DATEN and returns the decoded
+    /// answer, FEHLER included.
+    pub fn call<R, D>(
+        &self,
+        info: OkKommAktion,
+        request: R,
+        data: impl Into<Option<D>>,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<OkKommResponse>
+    where
+        R: WriteXml,
+        D: WriteXml,
+    {
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &request);
+        let soap_request = self.soap_body(info, request, data, apps_info)?;
+        let decoded = decode_transport_response(self.send_soap(soap_request));
+        finish_audit(self.audit.as_ref(), audit_record, &decoded);
+        let (info, daten) = decoded?;
+        Ok(OkKommResponse { info, daten })
+    }
+
+    pub fn send_request_xml<T, R>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<R>
+    where
+        T: WriteXml,
+        R: for<'a> Deserialize<'a>,
+    {
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &body);
+        let result = self.send_request(info, body, apps_info);
+        handle_decoded(
+            self.audit.as_ref(),
+            audit_record,
+            decode_transport_response(result),
+        )
+    }
+
+    pub fn send_request_xml_base64<T, R>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<R>
+    where
+        T: WriteXml,
+        R: for<'a> Deserialize<'a>,
+    {
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &body);
+        let soap_request = soap_body_base64(&self.defaults, info, body, apps_info)?;
+        let result = self.send_soap(soap_request);
+        handle_decoded(
+            self.audit.as_ref(),
+            audit_record,
+            decode_transport_response(result),
+        )
+    }
+
+    pub fn send_request_xml_in_content_container<T, R>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        attachments: Vec<ContentContainerAttachment>,
+        ref_id: String,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<R>
+    where
+        T: WriteXml,
+        R: for<'a> Deserialize<'a>,
+    {
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &body);
+        let soap_request = soap_body_content_container(
+            &self.defaults,
+            info,
+            body,
+            attachments,
+            ref_id,
+            apps_info,
+        )?;
+        let result = self.send_soap(soap_request);
+        handle_decoded(
+            self.audit.as_ref(),
+            audit_record,
+            decode_transport_response(result),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::Client;
+    use crate::config::{AppsInfoConfig, Defaults};
+    use crate::okkomm::{response_envelope, OkKommCallApplicationByteRequest};
+    use crate::soap::SoapResponse;
+    use crate::testing;
+    use crate::transport::{MemoryTransport, TransportResponse};
+    use crate::zkoxml::RawRequest;
+    use crate::OkKommAktion;
+
+    #[test]
+    fn test_blocking_call() -> anyhow::Result<()> {
+        let mut client = Client::from_transport(MemoryTransport::new(|body| {
+            let request = SoapResponse::<OkKommCallApplicationByteRequest>::from_str(
+                &String::from_utf8_lossy(&body),
+            )?
+            .into_inner()
+            .and_then(|request| request.zkocxml().ok().flatten())
+            .unwrap_or_default();
+            assert!(request.contains("<AKT_TECHUSER>portal</AKT_TECHUSER>"));
+            assert!(request.contains("<APPS_NAME>Bürgerportal</APPS_NAME>"));
+            assert!(request.contains("<SUCHE><MANDANTENANFRAGE/></SUCHE>"));
+            Ok(TransportResponse::new(
+                200,
+                response_envelope(testing::zkocxml("", "<OK/>").as_bytes()),
+            ))
+        }));
+        client.defaults = Defaults {
+            login: Some(("portal".to_owned(), "geheim".to_owned())),
+            apps_info: Some(AppsInfoConfig {
+                name: Some("Bürgerportal".to_owned()),
+                ..AppsInfoConfig::default()
+            }),
+        };
+        let response = client.call(
+            OkKommAktion::new(
+                "EWO".to_owned(),
+                "MANDANTENANFRAGE".to_owned(),
+                "ABRUFEN".to_owned(),
+                String::new(),
+            ),
+            RawRequest("<MANDANTENANFRAGE/>".to_owned()),
+            (),
+            None,
+        )?;
+        assert_eq!(response.daten.as_deref(), Some("<OK/>"));
+        Ok(())
+    }
+}
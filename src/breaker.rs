@@ -0,0 +1,252 @@
+//! Circuit breaker per target AGS of a [`Client`].
+//!
+//! If a municipality's backend is down, waiting for the full timeout on every
+//! request blocks the callers. After [`BreakerConfig::failure_threshold`]
+//! consecutive failures for an AGS the breaker opens and requests fail
+//! immediately with [`BreakerError::Open`]. After
+//! [`BreakerConfig::open_for`] up to [`BreakerConfig::half_open_probes`]
+//! requests are let through; if they all succeed the breaker closes again,
+//! one failure opens it for another period.
+//!
+//! Failures are requests without an answer and HTTP 5xx answers, not FEHLER.
+//! Like the [`limits`](crate::limits), a breaker belongs to one `Client` and
+//! so to one endpoint; [`Client::send_soap`] uses the endpoint wide state
+//! as its target AGS is not known. The blocking client and
+//! `Client::service` have no circuit breaker.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::limits::secs;
+use crate::Client;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BreakerError {
+    #[error("circuit breaker for AGS {ziel_ags:?} is open, retry in {retry_after:?}")]
+    Open {
+        /// `None` for the endpoint wide breaker.
+        ziel_ags: Option<String>,
+        retry_after: Duration,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BreakerConfig {
+    /// Consecutive failures that open the breaker.
+    pub failure_threshold: u32,
+    #[serde(rename = "open_secs", deserialize_with = "secs")]
+    pub open_for: Duration,
+    /// Successful probes needed to close the breaker again.
+    pub half_open_probes: u32,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_for: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probing: u32, succeeded: u32 },
+}
+
+#[derive(Debug)]
+pub(crate) struct Breaker {
+    config: BreakerConfig,
+    states: Mutex<HashMap<Option<String>, State>>,
+}
+
+/// Outcome of a request let through by the breaker. Dropping it without
+/// [`Permit::record`], e.g. because the request was cancelled, counts as
+/// neither success nor failure.
+pub(crate) struct Permit {
+    breaker: Arc<Breaker>,
+    ziel_ags: Option<String>,
+    recorded: bool,
+}
+
+impl Breaker {
+    pub fn new(config: &BreakerConfig) -> Self {
+        Self {
+            config: config.clone(),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(self: &Arc<Self>, ziel_ags: Option<&str>) -> Result<Permit, BreakerError> {
+        let now = Instant::now();
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let state = states
+            .entry(ziel_ags.map(ToOwned::to_owned))
+            .or_insert(State::Closed { failures: 0 });
+        let open = |retry_after| BreakerError::Open {
+            ziel_ags: ziel_ags.map(ToOwned::to_owned),
+            retry_after,
+        };
+        match *state {
+            State::Closed { .. } => {}
+            State::Open { until } if now < until => return Err(open(until - now)),
+            State::Open { .. } => {
+                *state = State::HalfOpen {
+                    probing: 1,
+                    succeeded: 0,
+                }
+            }
+            State::HalfOpen { probing, succeeded }
+                if probing + succeeded < self.config.half_open_probes =>
+            {
+                *state = State::HalfOpen {
+                    probing: probing + 1,
+                    succeeded,
+                }
+            }
+            State::HalfOpen { .. } => return Err(open(Duration::ZERO)),
+        }
+        Ok(Permit {
+            breaker: self.clone(),
+            ziel_ags: ziel_ags.map(ToOwned::to_owned),
+            recorded: false,
+        })
+    }
+
+    fn finish(&self, ziel_ags: &Option<String>, success: Option<bool>) {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = states.get_mut(ziel_ags) else {
+            return;
+        };
+        let open = State::Open {
+            until: Instant::now() + self.config.open_for,
+        };
+        *state = match (*state, success) {
+            (State::Closed { .. }, Some(true)) => State::Closed { failures: 0 },
+            (State::Closed { failures }, Some(false)) => {
+                if failures + 1 >= self.config.failure_threshold {
+                    open
+                } else {
+                    State::Closed {
+                        failures: failures + 1,
+                    }
+                }
+            }
+            (State::HalfOpen { probing, succeeded }, Some(true)) => {
+                if succeeded + 1 >= self.config.half_open_probes {
+                    State::Closed { failures: 0 }
+                } else {
+                    State::HalfOpen {
+                        probing: probing.saturating_sub(1),
+                        succeeded: succeeded + 1,
+                    }
+                }
+            }
+            (State::HalfOpen { .. }, Some(false)) => open,
+            (State::HalfOpen { probing, succeeded }, None) => State::HalfOpen {
+                probing: probing.saturating_sub(1),
+                succeeded,
+            },
+            (state, _) => state,
+        };
+    }
+}
+
+impl Permit {
+    pub fn record(mut self, success: bool) {
+        self.recorded = true;
+        self.breaker.finish(&self.ziel_ags, Some(success));
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.breaker.finish(&self.ziel_ags, None);
+        }
+    }
+}
+
+impl Client {
+    /// Opens a circuit breaker per target AGS, see [`BreakerConfig`]. Clones
+    /// of the client share the breakers.
+    pub fn with_circuit_breaker(mut self, config: &BreakerConfig) -> Self {
+        self.breaker = Some(Arc::new(Breaker::new(config)));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{BreakerConfig, BreakerError};
+    use crate::okkomm::response_envelope;
+    use crate::transport::{MemoryTransport, TransportResponse};
+    use crate::zkoxml::RawRequest;
+    use crate::{testing, Client, OkKommAktion};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_circuit_breaker() {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let counter = sent.clone();
+        let client = Client::from_transport(MemoryTransport::new(move |_| {
+            // the backend is down for the first two requests
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Ok(TransportResponse::new(503, "Service Unavailable")),
+                _ => Ok(TransportResponse::new(
+                    200,
+                    response_envelope(testing::zkocxml("", "<OK/>").as_bytes()),
+                )),
+            }
+        }))
+        .with_circuit_breaker(&BreakerConfig {
+            failure_threshold: 2,
+            open_for: Duration::from_secs(10),
+            half_open_probes: 1,
+        });
+        let call = |ziel_ags: &str| {
+            client.call(
+                OkKommAktion::new(
+                    "EWO".to_owned(),
+                    "MANDANTENANFRAGE".to_owned(),
+                    "ABRUFEN".to_owned(),
+                    ziel_ags.to_owned(),
+                ),
+                RawRequest(String::new()),
+                (),
+                None,
+            )
+        };
+
+        assert!(call("09162000").await.is_err());
+        assert!(call("09162000").await.is_err());
+        let err = call("09162000").await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<BreakerError>(),
+            Some(&BreakerError::Open {
+                ziel_ags: Some("09162000".to_owned()),
+                retry_after: Duration::from_secs(10),
+            })
+        );
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+
+        // other municipalities are not affected
+        assert!(call("05315000").await.is_ok());
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert!(call("09162000").await.is_ok());
+        assert!(call("09162000").await.is_ok());
+        assert_eq!(sent.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,480 @@
+//! Response cache for idempotent lookups of a [`Client`].
+//!
+//! Catalogue lookups return the same data for hours. With a [`CacheConfig`]
+//! the answers of [`Client::call`] (and so of the typed requests) are cached
+//! for the TTL of a [`CacheRule`] for their AKTION. Caching is opt-in per
+//! Verfahren, Typ and Ausführung: other AKTIONen, calls with DATEN, typed
+//! requests that change data and [`Client::uebermittlungssperren`] are never
+//! cached. Mandantenanfragen have their own cache, see
+//! [`Client::with_mandanten_cache`]. A cached answer is not invalidated by
+//! changes through other AKTIONen and may be stale for up to its TTL.
+//!
+//! The key is a SHA-256 hash of the AKTION, the AKT_LOGIN user, the
+//! APPS_KENNUNG and the SUCHE, without comments and whitespace between
+//! elements, so it does not contain personal data and callers with other
+//! credentials or APPS_KENNUNG do not share answers. Answers with a FEHLER
+//! are only cached if [`CacheConfig::cache_fehler`] is set. Answers from the
+//! cache are audited like sent requests.
+//!
+//! ```toml
+//! [response_cache]
+//! capacity = 500
+//! rules = [
+//!     { verfahren = "EWO", typ = "KATALOG", ausfuehrung = "ABRUFEN", ttl_secs = 600 },
+//! ]
+//! ```
+//!
+//! Answers are kept in a [`MemoryStore`] by default; other stores, e.g.
+//! shared by several instances, implement [`ResponseStore`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+
+use crate::limits::secs;
+use crate::redact;
+use crate::response::OkKommResponse;
+use crate::transport::{BoxFuture, TransportResponse};
+use crate::xml::{self, WriteXml};
+use crate::zkoxml::AppsInfo;
+use crate::{Client, OkKommAktion};
+
+/// Storage of cached answers, the raw SOAP envelopes, by key.
+pub trait ResponseStore: Send + Sync {
+    fn get(&self, key: &str) -> BoxFuture<'_, Option<Bytes>>;
+
+    /// Stores `body` under `key`, to be dropped after `ttl`.
+    fn put(&self, key: String, body: Bytes, ttl: Duration) -> BoxFuture<'_, ()>;
+}
+
+/// In-memory store dropping the least recently used answers beyond its
+/// capacity.
+#[derive(Debug)]
+pub struct MemoryStore {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    tick: u64,
+    /// Expiry, last use and answer per key.
+    entries: HashMap<String, (Instant, u64, Bytes)>,
+    by_use: BTreeMap<u64, String>,
+}
+
+impl MemoryStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Lru::default()),
+        }
+    }
+
+    fn get_now(&self, key: &str) -> Option<Bytes> {
+        let mut lru = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let Lru {
+            tick,
+            entries,
+            by_use,
+        } = &mut *lru;
+        let (expires, used, body) = entries.get_mut(key)?;
+        by_use.remove(used);
+        if *expires <= Instant::now() {
+            entries.remove(key);
+            return None;
+        }
+        *tick += 1;
+        *used = *tick;
+        by_use.insert(*tick, key.to_owned());
+        Some(body.clone())
+    }
+
+    fn put_now(&self, key: String, body: Bytes, ttl: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut lru = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let Lru {
+            tick,
+            entries,
+            by_use,
+        } = &mut *lru;
+        *tick += 1;
+        by_use.insert(*tick, key.clone());
+        if let Some((_, used, _)) = entries.insert(key, (Instant::now() + ttl, *tick, body)) {
+            by_use.remove(&used);
+        }
+        while entries.len() > self.capacity {
+            let Some((_, key)) = by_use.pop_first() else {
+                break;
+            };
+            entries.remove(&key);
+        }
+    }
+}
+
+impl ResponseStore for MemoryStore {
+    fn get(&self, key: &str) -> BoxFuture<'_, Option<Bytes>> {
+        let body = self.get_now(key);
+        Box::pin(async move { Ok(body) })
+    }
+
+    fn put(&self, key: String, body: Bytes, ttl: Duration) -> BoxFuture<'_, ()> {
+        self.put_now(key, body, ttl);
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// TTL for the answers of one AKTION.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CacheRule {
+    pub verfahren: String,
+    pub typ: String,
+    pub ausfuehrung: String,
+    #[serde(rename = "ttl_secs", deserialize_with = "secs")]
+    pub ttl: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Answers kept by the in-memory store.
+    pub capacity: usize,
+    pub rules: Vec<CacheRule>,
+    /// Also cache answers with a FEHLER.
+    pub cache_fehler: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            rules: Vec::new(),
+            cache_fehler: false,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Caches the answers for `verfahren`, `typ` and `ausfuehrung` for
+    /// `ttl`.
+    pub fn with_ttl(
+        mut self,
+        verfahren: impl Into<String>,
+        typ: impl Into<String>,
+        ausfuehrung: impl Into<String>,
+        ttl: Duration,
+    ) -> Self {
+        self.rules.push(CacheRule {
+            verfahren: verfahren.into(),
+            typ: typ.into(),
+            ausfuehrung: ausfuehrung.into(),
+            ttl,
+        });
+        self
+    }
+
+    fn ttl(&self, info: &OkKommAktion) -> Option<Duration> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.verfahren == info.verfahren
+                    && rule.typ == info.typ
+                    && rule.ausfuehrung == info.ausfuehrung
+            })
+            .map(|rule| rule.ttl)
+            .filter(|ttl| !ttl.is_zero())
+    }
+}
+
+pub(crate) struct ResponseCache {
+    config: CacheConfig,
+    store: Arc<dyn ResponseStore>,
+}
+
+/// Cache slot of one request.
+pub(crate) struct CacheEntry<'a> {
+    cache: &'a ResponseCache,
+    key: String,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    /// Slot for `info` and `suche` sent with the AKT_LOGIN user `techuser`
+    /// and `apps_info`, `None` if the answer is not cached.
+    pub fn entry<T, D>(
+        &self,
+        info: &OkKommAktion,
+        techuser: Option<&str>,
+        apps_info: Option<&AppsInfo>,
+        suche: &T,
+        daten: Option<&D>,
+    ) -> Option<CacheEntry<'_>>
+    where
+        T: WriteXml,
+        D: WriteXml,
+    {
+        let ttl = self.config.ttl(info)?;
+        if daten.is_some_and(|daten| xml::to_bytes(daten).map_or(true, |d| !d.is_empty())) {
+            return None;
+        }
+        let suche = match xml::to_bytes(suche)
+            .and_then(|suche| xml::normalize(&String::from_utf8_lossy(&suche)))
+        {
+            Ok(suche) => suche,
+            Err(err) => {
+                log::error!(
+                    "{}",
+                    redact::text(&format!(
+                        "Error while normalising SUCHE for response cache: {err:#?}"
+                    ))
+                );
+                return None;
+            }
+        };
+        let mut hasher = Sha256::new();
+        for field in [
+            &info.verfahren,
+            &info.typ,
+            &info.ausfuehrung,
+            &info.ziel_ags,
+        ] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        let kennung = apps_info.and_then(|apps_info| apps_info.kennung.as_deref());
+        for field in [techuser, kennung] {
+            hasher.update(field.unwrap_or_default().as_bytes());
+            hasher.update([u8::from(field.is_some())]);
+        }
+        hasher.update(suche.as_bytes());
+        Some(CacheEntry {
+            cache: self,
+            key: format!("{:x}", hasher.finalize()),
+            ttl,
+        })
+    }
+}
+
+impl CacheEntry<'_> {
+    /// Cached answer, errors of the store count as a miss.
+    pub async fn get(&self) -> Option<TransportResponse> {
+        match self.cache.store.get(&self.key).await {
+            Ok(body) => body.map(|body| TransportResponse::new(200, body)),
+            Err(err) => {
+                log::error!(
+                    "{}",
+                    redact::text(&format!("Error while reading response cache: {err:#?}"))
+                );
+                None
+            }
+        }
+    }
+
+    /// Stores `body` if `response`, decoded from it, may be cached.
+    pub async fn put(self, body: Bytes, response: &OkKommResponse) {
+        if response.error().is_some() && !self.cache.config.cache_fehler {
+            return;
+        }
+        if let Err(err) = self.cache.store.put(self.key, body, self.ttl).await {
+            log::error!(
+                "{}",
+                redact::text(&format!("Error while writing response cache: {err:#?}"))
+            );
+        }
+    }
+}
+
+impl Client {
+    /// Caches answers in memory, see [`CacheConfig`]. Clones of the client
+    /// share the cache.
+    pub fn with_response_cache(self, config: &CacheConfig) -> Self {
+        let store = MemoryStore::new(config.capacity);
+        self.with_response_store(config, store)
+    }
+
+    /// Caches answers in `store`; [`CacheConfig::capacity`] is not used.
+    pub fn with_response_store(
+        mut self,
+        config: &CacheConfig,
+        store: impl ResponseStore + 'static,
+    ) -> Self {
+        self.response_cache = Some(Arc::new(ResponseCache {
+            config: config.clone(),
+            store: Arc::new(store),
+        }));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chrono::{NaiveDate, Utc};
+    use chrono_tz::Europe::Berlin;
+
+    use super::{CacheConfig, MemoryStore};
+    use crate::verkehr::bewohnerparken::{self, BewohnerparkausweisAntrag};
+    use crate::zkoxml::{AppsInfo, RawRequest};
+    use crate::{testing, Client, OkKommAktion};
+
+    fn counting_client(antwort: &str) -> (Client, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = testing::client(testing::zkocxml(antwort, "<KATALOG/>"), move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .with_response_cache(&CacheConfig::default().with_ttl(
+            "EWO",
+            "KATALOG",
+            "ABRUFEN",
+            Duration::from_secs(60),
+        ));
+        (client, calls)
+    }
+
+    async fn katalog(client: &Client, typ: &str, suche: &str) -> anyhow::Result<()> {
+        katalog_als(client, typ, "ABRUFEN", suche, None).await
+    }
+
+    async fn katalog_als(
+        client: &Client,
+        typ: &str,
+        ausfuehrung: &str,
+        suche: &str,
+        kennung: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let aktion = OkKommAktion::new(
+            "EWO".to_owned(),
+            typ.to_owned(),
+            ausfuehrung.to_owned(),
+            "09162000".to_owned(),
+        );
+        let apps_info = kennung.map(|kennung| AppsInfo {
+            kennung: Some(kennung.to_owned()),
+            ..AppsInfo::default_at(&Utc::now().with_timezone(&Berlin))
+        });
+        client
+            .call(aktion, RawRequest(suche.to_owned()), (), apps_info)
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_response_cache() -> anyhow::Result<()> {
+        let (client, calls) = counting_client("");
+        katalog(
+            &client,
+            "KATALOG",
+            "<KATALOG><NAME>STAAT</NAME><ALLE/></KATALOG>",
+        )
+        .await?;
+        katalog(
+            &client,
+            "KATALOG",
+            "<KATALOG>\n  <NAME>STAAT</NAME>\n  <!-- alle --><ALLE/>\n</KATALOG>",
+        )
+        .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        katalog(
+            &client,
+            "KATALOG",
+            "<KATALOG><NAME>FAMILIENSTAND</NAME></KATALOG>",
+        )
+        .await?;
+        // no TTL configured
+        katalog(&client, "AUSKUNFT", "<KATALOG><NAME>STAAT</NAME></KATALOG>").await?;
+        katalog(&client, "AUSKUNFT", "<KATALOG><NAME>STAAT</NAME></KATALOG>").await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        // other Ausführung of the same Typ
+        let staat = "<KATALOG><NAME>STAAT</NAME><ALLE/></KATALOG>";
+        katalog_als(&client, "KATALOG", "AENDERN", staat, None).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        // callers with another APPS_KENNUNG do not share answers
+        katalog_als(&client, "KATALOG", "ABRUFEN", staat, Some("portal-a")).await?;
+        katalog_als(&client, "KATALOG", "ABRUFEN", staat, Some("portal-a")).await?;
+        katalog_als(&client, "KATALOG", "ABRUFEN", staat, Some("portal-b")).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 7);
+        // nor do clients with other credentials sharing the cache
+        let (other, other_calls) = counting_client("");
+        let mut other = other;
+        other.response_cache = client.response_cache.clone();
+        other.defaults.login = Some(("techuser".to_owned(), "geheim".to_owned()));
+        katalog(&other, "KATALOG", staat).await?;
+        assert_eq!(other_calls.load(Ordering::SeqCst), 1);
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        katalog(
+            &client,
+            "KATALOG",
+            "<KATALOG><NAME>STAAT</NAME><ALLE/></KATALOG>",
+        )
+        .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 8);
+
+        let (client, calls) = counting_client(&testing::fehler("F", "Systemfehler"));
+        katalog(&client, "KATALOG", "<KATALOG/>").await?;
+        katalog(&client, "KATALOG", "<KATALOG/>").await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let store = MemoryStore::new(2);
+        for key in ["a", "b", "c"] {
+            store.put_now(key.to_owned(), key.into(), Duration::from_secs(60));
+            store.get_now("a");
+        }
+        assert!(store.get_now("a").is_some());
+        assert!(store.get_now("b").is_none());
+        assert!(store.get_now("c").is_some());
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_mutating_aktion_bypasses_cache() -> anyhow::Result<()> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<BEWOHNERPARKAUSWEIS><AUSWEISNUMMER>BP-2025-123</AUSWEISNUMMER><ZONE>M-12</ZONE><GUELTIG_AB>01.04.2025</GUELTIG_AB><GUELTIG_BIS>31.03.2026</GUELTIG_BIS></BEWOHNERPARKAUSWEIS>",
+            ),
+            move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        )
+        .with_response_cache(&CacheConfig::default().with_ttl(
+            "VERKEHR",
+            bewohnerparken::TYP,
+            bewohnerparken::BEANTRAGEN,
+            Duration::from_secs(60),
+        ));
+        let antrag = BewohnerparkausweisAntrag {
+            bewohner: testing::person(),
+            anschrift: testing::anschrift(),
+            zone: "M-12".to_owned(),
+            kennzeichen: "K-AB 1234".to_owned(),
+            halter: true,
+            gueltig_ab: NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            gueltig_bis: NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            bisherige_ausweisnummer: None,
+        };
+        // an Antrag is sent every time, even with a rule for its AKTION
+        client
+            .bewohnerparkausweis("05315000", &antrag, None)
+            .await?;
+        client
+            .bewohnerparkausweis("05315000", &antrag, None)
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,497 @@
+//! Client configuration from TOML, YAML and environment variables.
+//!
+//! ```toml
+//! url = "https://okkomm.example.org/okkomm/services/KomService"
+//! proxy = "http://proxy.example.org:3128"
+//! timeout_secs = 30
+//!
+//! [tls]
+//! ca_certs = ["/etc/okkomm/ca.pem"]
+//! client_cert = "/etc/okkomm/client.pem"
+//! client_key = "/run/secrets/okkomm-client-key"
+//!
+//! [credentials]
+//! techuser = "portal"
+//! techpwd_file = "/run/secrets/okkomm-techpwd"
+//!
+//! [apps_info]
+//! name = "Bürgerportal"
+//! version = "2.1.0"
+//! ```
+//!
+//! Files are parsed with the `toml` and `yaml` cargo features. Every setting
+//! can also be given as environment variable, see [`ClientConfig::with_env`].
+//! Secrets are never required inline: the technical password can be read
+//! from `techpwd_file`, the TLS key always is a file.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+use chrono_tz::Europe::Berlin;
+
+use crate::breaker::BreakerConfig;
+use crate::cache::CacheConfig;
+use crate::limits::Limits;
+use crate::redact;
+use crate::tls::ClientIdentity;
+use crate::transport::{client_builder, ReqwestTransport};
+use crate::zkoxml::AppsInfo;
+use crate::Client;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("cannot read {path}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[cfg(feature = "toml")]
+    #[error("invalid TOML configuration")]
+    Toml(#[from] toml::de::Error),
+    #[cfg(feature = "yaml")]
+    #[error("invalid YAML configuration")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error(
+        "unsupported configuration file {0}, expected .toml or .yaml with the matching feature"
+    )]
+    Format(PathBuf),
+    #[error("environment variable {var}: {reason}")]
+    Env { var: String, reason: &'static str },
+    #[error("{0} is missing")]
+    Missing(&'static str),
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, ConfigError> {
+    std::fs::read(path).map_err(|source| ConfigError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// Parses a `.toml`, `.yaml` or `.yml` file, depending on the enabled
+/// features.
+pub(crate) fn parse_file<T>(path: &Path) -> Result<T, ConfigError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    match path.extension().and_then(|e| e.to_str()) {
+        #[cfg(feature = "toml")]
+        Some("toml") => Ok(toml::from_str(&String::from_utf8_lossy(&read(path)?))?),
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => Ok(serde_yaml::from_slice(&read(path)?)?),
+        _ => Err(ConfigError::Format(path.to_owned())),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM root certificates trusted in addition to the built-in ones.
+    pub ca_certs: Vec<PathBuf>,
+    /// PEM client certificate for mutual TLS.
+    pub client_cert: Option<PathBuf>,
+    /// PKCS#8 PEM key of `client_cert`.
+    pub client_key: Option<PathBuf>,
+}
+
+/// AKT_TECHUSER and AKT_TECHPWD sent in every ZKOCXML.
+#[derive(Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Credentials {
+    pub techuser: String,
+    pub techpwd: Option<String>,
+    /// File holding the password, trailing whitespace is ignored. Takes
+    /// precedence over `techpwd`.
+    pub techpwd_file: Option<PathBuf>,
+}
+
+impl Credentials {
+    /// The password, read from `techpwd_file` if set.
+    pub fn techpwd(&self) -> Result<String, ConfigError> {
+        match (&self.techpwd_file, &self.techpwd) {
+            (Some(path), _) => Ok(String::from_utf8_lossy(&read(path)?).trim_end().to_owned()),
+            (None, Some(techpwd)) => Ok(techpwd.clone()),
+            (None, None) => Err(ConfigError::Missing("techpwd")),
+        }
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("techuser", &self.techuser)
+            .field("techpwd", &redact::opt("AKT_TECHPWD", &self.techpwd))
+            .field("techpwd_file", &self.techpwd_file)
+            .finish()
+    }
+}
+
+/// APPS_INFO sent when a call passes none. Unset fields keep the crate's
+/// defaults; APPS_DATUM and APPS_UHRZEIT are always the time of the call.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppsInfoConfig {
+    pub typ: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub kennung: Option<String>,
+    pub source_id: Option<String>,
+    pub ip_adresse: Option<String>,
+    pub ziel_url: Option<String>,
+    pub return_queue: Option<String>,
+}
+
+impl AppsInfoConfig {
+    fn fields(&mut self) -> [(&'static str, &mut Option<String>); 8] {
+        [
+            ("TYP", &mut self.typ),
+            ("NAME", &mut self.name),
+            ("VERSION", &mut self.version),
+            ("KENNUNG", &mut self.kennung),
+            ("SOURCE_ID", &mut self.source_id),
+            ("IP_ADRESSE", &mut self.ip_adresse),
+            ("ZIEL_URL", &mut self.ziel_url),
+            ("RETURN_QUEUE", &mut self.return_queue),
+        ]
+    }
+
+    fn to_apps_info(&self) -> AppsInfo {
+        let mut apps_info = AppsInfo::default_at(&Utc::now().with_timezone(&Berlin));
+        for (value, target) in [
+            (&self.typ, &mut apps_info.typ),
+            (&self.name, &mut apps_info.name),
+            (&self.version, &mut apps_info.version),
+            (&self.kennung, &mut apps_info.kennung),
+            (&self.source_id, &mut apps_info.source_id),
+            (&self.ip_adresse, &mut apps_info.ip_adresse),
+            (&self.ziel_url, &mut apps_info.ziel_url),
+            (&self.return_queue, &mut apps_info.return_queue),
+        ] {
+            if value.is_some() {
+                target.clone_from(value);
+            }
+        }
+        apps_info
+    }
+}
+
+/// Settings of a [`Client`] talking to one OK.KOMM endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    pub url: String,
+    /// HTTP(S) proxy, e.g. `http://proxy:3128`. System proxies are ignored.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub tls: TlsConfig,
+    #[serde(default)]
+    pub credentials: Option<Credentials>,
+    #[serde(default)]
+    pub apps_info: Option<AppsInfoConfig>,
+    #[serde(default)]
+    pub limits: Option<Limits>,
+    #[serde(default)]
+    pub circuit_breaker: Option<BreakerConfig>,
+    #[serde(default)]
+    pub response_cache: Option<CacheConfig>,
+}
+
+impl ClientConfig {
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self, ConfigError> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Reads a `.toml`, `.yaml` or `.yml` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        parse_file(path.as_ref())
+    }
+
+    /// Configuration from environment variables only, see
+    /// [`ClientConfig::with_env`].
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        Self::default().with_env(prefix)
+    }
+
+    /// Overrides settings with the environment variables `<prefix>URL`,
+    /// `PROXY`, `TIMEOUT_SECS`, `CA_CERTS` (a path list), `CLIENT_CERT`,
+    /// `CLIENT_KEY`, `TECHUSER`, `TECHPWD`, `TECHPWD_FILE` and `APPS_<field>`,
+    /// e.g. `OKKOMM_APPS_NAME` for the prefix `OKKOMM_`.
+    pub fn with_env(mut self, prefix: &str) -> Result<Self, ConfigError> {
+        let var = |name: &str| -> Result<Option<String>, ConfigError> {
+            let var = format!("{prefix}{name}");
+            match std::env::var(&var) {
+                Ok(value) => Ok(Some(value)),
+                Err(std::env::VarError::NotPresent) => Ok(None),
+                Err(std::env::VarError::NotUnicode(_)) => Err(ConfigError::Env {
+                    var,
+                    reason: "not valid unicode",
+                }),
+            }
+        };
+
+        if let Some(url) = var("URL")? {
+            self.url = url;
+        }
+        if let Some(proxy) = var("PROXY")? {
+            self.proxy = Some(proxy);
+        }
+        if let Some(timeout) = var("TIMEOUT_SECS")? {
+            self.timeout_secs = Some(timeout.parse().map_err(|_| ConfigError::Env {
+                var: format!("{prefix}TIMEOUT_SECS"),
+                reason: "expected seconds",
+            })?);
+        }
+        if let Some(ca_certs) = var("CA_CERTS")? {
+            self.tls.ca_certs = std::env::split_paths(&ca_certs).collect();
+        }
+        if let Some(client_cert) = var("CLIENT_CERT")? {
+            self.tls.client_cert = Some(client_cert.into());
+        }
+        if let Some(client_key) = var("CLIENT_KEY")? {
+            self.tls.client_key = Some(client_key.into());
+        }
+        if let Some(techuser) = var("TECHUSER")? {
+            self.credentials
+                .get_or_insert_with(Default::default)
+                .techuser = techuser;
+        }
+        if let Some(techpwd) = var("TECHPWD")? {
+            self.credentials
+                .get_or_insert_with(Default::default)
+                .techpwd = Some(techpwd);
+        }
+        if let Some(techpwd_file) = var("TECHPWD_FILE")? {
+            self.credentials
+                .get_or_insert_with(Default::default)
+                .techpwd_file = Some(techpwd_file.into());
+        }
+        let mut apps_info = self.apps_info.take().unwrap_or_default();
+        for (name, field) in apps_info.fields() {
+            if let Some(value) = var(&format!("APPS_{name}"))? {
+                *field = Some(value);
+            }
+        }
+        if apps_info != AppsInfoConfig::default() {
+            self.apps_info = Some(apps_info);
+        }
+        Ok(self)
+    }
+
+    fn identity(&self) -> Result<Option<ClientIdentity>, ConfigError> {
+        match (&self.tls.client_cert, &self.tls.client_key) {
+            (Some(cert), Some(key)) => Ok(Some(ClientIdentity::new(read(cert)?, read(key)?))),
+            (Some(_), None) => Err(ConfigError::Missing("tls.client_key")),
+            (None, Some(_)) => Err(ConfigError::Missing("tls.client_cert")),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// URL, CA certificates and client identity, checked for completeness.
+    fn endpoint(&self) -> Result<(Vec<Vec<u8>>, Option<ClientIdentity>), ConfigError> {
+        if self.url.is_empty() {
+            return Err(ConfigError::Missing("url"));
+        }
+        let ca_certs = self
+            .tls
+            .ca_certs
+            .iter()
+            .map(|path| read(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((ca_certs, self.identity()?))
+    }
+
+    /// Transport with the configured TLS, proxy and timeout settings.
+    pub fn transport(&self) -> anyhow::Result<ReqwestTransport> {
+        let (ca_certs, identity) = self.endpoint()?;
+        let mut builder = client_builder(Some(ca_certs), identity)?;
+        if let Some(proxy) = self.proxy.as_deref() {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(timeout) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        Ok(ReqwestTransport::from_client(
+            builder.build()?,
+            self.url.clone(),
+        ))
+    }
+
+    /// [`ClientConfig::transport`] for the [`blocking`](crate::blocking)
+    /// client.
+    #[cfg(feature = "blocking")]
+    pub fn blocking_transport(&self) -> anyhow::Result<crate::blocking::ReqwestTransport> {
+        let (ca_certs, identity) = self.endpoint()?;
+        let mut builder = crate::blocking::client_builder(Some(ca_certs), identity)?;
+        if let Some(proxy) = self.proxy.as_deref() {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(timeout) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        Ok(crate::blocking::ReqwestTransport::from_client(
+            builder.build()?,
+            self.url.clone(),
+        ))
+    }
+
+    pub(crate) fn defaults(&self) -> Result<Defaults, ConfigError> {
+        let login = match self.credentials.as_ref() {
+            Some(credentials) => Some((credentials.techuser.clone(), credentials.techpwd()?)),
+            None => None,
+        };
+        Ok(Defaults {
+            login,
+            apps_info: self.apps_info.clone(),
+        })
+    }
+}
+
+/// Login and APPS_INFO a client puts into every ZKOCXML.
+#[derive(Clone, Default)]
+pub(crate) struct Defaults {
+    pub login: Option<(String, String)>,
+    pub apps_info: Option<AppsInfoConfig>,
+}
+
+impl Defaults {
+    /// `apps_info` of the call, or the configured one.
+    pub fn apps_info(&self, apps_info: Option<AppsInfo>) -> Option<AppsInfo> {
+        apps_info.or_else(|| self.apps_info.as_ref().map(AppsInfoConfig::to_apps_info))
+    }
+}
+
+impl Client {
+    /// Sends `credentials` as AKT_LOGIN in every ZKOCXML.
+    pub fn with_credentials(mut self, credentials: &Credentials) -> Result<Self, ConfigError> {
+        self.defaults.login = Some((credentials.techuser.clone(), credentials.techpwd()?));
+        Ok(self)
+    }
+
+    /// Client for the endpoint, credentials, APPS_INFO, limits, circuit
+    /// breaker and response cache of `config`.
+    pub fn from_config(config: &ClientConfig) -> anyhow::Result<Self> {
+        let mut client = Self::from_transport(config.transport()?);
+        client.defaults = config.defaults()?;
+        if let Some(limits) = config.limits.as_ref() {
+            client = client.with_limits(limits)?;
+        }
+        if let Some(breaker) = config.circuit_breaker.as_ref() {
+            client = client.with_circuit_breaker(breaker);
+        }
+        if let Some(cache) = config.response_cache.as_ref() {
+            client = client.with_response_cache(cache);
+        }
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClientConfig;
+    use crate::okkomm::OkKommCallApplicationByteRequest;
+    use crate::soap::SoapResponse;
+    use crate::zkoxml::RawRequest;
+    use crate::{Client, OkKommAktion};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_from_config() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let techpwd_file =
+            std::env::temp_dir().join(format!("okkomm-techpwd-{}", std::process::id()));
+        std::fs::write(&techpwd_file, "geheim\n")?;
+        std::env::set_var("OKKOMM_TEST_CONFIG_URL", "http://localhost:8380/KomService");
+        std::env::set_var("OKKOMM_TEST_CONFIG_TECHUSER", "portal");
+        std::env::set_var("OKKOMM_TEST_CONFIG_TECHPWD_FILE", &techpwd_file);
+        std::env::set_var("OKKOMM_TEST_CONFIG_APPS_NAME", "Bürgerportal");
+        let config = ClientConfig::from_env("OKKOMM_TEST_CONFIG_")?;
+        assert_eq!(config.url, "http://localhost:8380/KomService");
+
+        let client = Client::from_config(&config)?;
+        std::fs::remove_file(&techpwd_file)?;
+        let soap = client
+            .soap_body(
+                OkKommAktion::new(
+                    "EWO".to_owned(),
+                    "MANDANTENANFRAGE".to_owned(),
+                    "ABRUFEN".to_owned(),
+                    "".to_owned(),
+                ),
+                RawRequest("<MANDANTENANFRAGE/>".to_owned()),
+                (),
+                None,
+            )?
+            .to_message()?;
+        let request = SoapResponse::<OkKommCallApplicationByteRequest>::from_str(
+            &String::from_utf8_lossy(&soap),
+        )?
+        .into_inner()
+        .unwrap()
+        .zkocxml()?
+        .unwrap();
+        assert!(request.contains(
+            "<AKT_LOGIN><AKT_TECHUSER>portal</AKT_TECHUSER><AKT_TECHPWD>geheim</AKT_TECHPWD></AKT_LOGIN>"
+        ));
+        assert!(request.contains("<APPS_NAME>Bürgerportal</APPS_NAME>"));
+
+        assert!(ClientConfig::from_env("OKKOMM_TEST_CONFIG_UNSET_")
+            .is_ok_and(|c| Client::from_config(&c).is_err()));
+        Ok(())
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use crate::limits::Rate;
+        use std::time::Duration;
+
+        let config = ClientConfig::from_toml(
+            r#"
+            url = "https://okkomm.example.org/KomService"
+            timeout_secs = 30
+
+            [credentials]
+            techuser = "portal"
+            techpwd = "geheim"
+
+            [apps_info]
+            version = "2.1.0"
+
+            [limits]
+            max_in_flight = 4
+            ags_rate = { requests = 2, per_secs = 1 }
+            queue_timeout_secs = 2.5
+
+            [circuit_breaker]
+            failure_threshold = 3
+
+            [response_cache]
+            rules = [{ verfahren = "EWO", typ = "KATALOG", ausfuehrung = "ABRUFEN", ttl_secs = 3600 }]
+            "#,
+        )?;
+        assert_eq!(config.timeout_secs, Some(30));
+        assert!(!format!("{config:?}").contains("geheim"));
+        assert_eq!(config.apps_info.unwrap().version.as_deref(), Some("2.1.0"));
+        let limits = config.limits.unwrap();
+        assert_eq!(limits.ags_rate, Some(Rate::per_second(2)));
+        assert_eq!(limits.queue_timeout, Duration::from_millis(2500));
+        let breaker = config.circuit_breaker.unwrap();
+        assert_eq!(breaker.failure_threshold, 3);
+        assert_eq!(breaker.open_for, Duration::from_secs(30));
+        let cache = config.response_cache.unwrap();
+        assert_eq!(cache.capacity, 1000);
+        assert_eq!(cache.rules[0].ttl, Duration::from_secs(3600));
+        assert!(ClientConfig::from_toml("url = \"x\"\nurll = \"y\"").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+//! Typed requests for the EWO (Einwohnerwesen) Verfahren.
+
+use chrono::NaiveDate;
+
+use crate::redact;
+use crate::validation::{self, Validate, ValidationError};
+use crate::xml::{datum, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, Error};
+
+pub mod anmeldung;
+pub mod auskunft;
+pub mod meldebescheinigung;
+pub mod sperren;
+pub mod wahlschein;
+
+pub const VERFAHREN: &str = "EWO";
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Person {
+    #[serde(rename = "NAME")]
+    pub name: String,
+    #[serde(rename = "VORNAME")]
+    pub vorname: String,
+    #[serde(rename = "GEBURTSNAME", default)]
+    pub geburtsname: Option<String>,
+    #[serde(rename = "GEBURTSDATUM", deserialize_with = "datum::deserialize")]
+    pub geburtsdatum: NaiveDate,
+}
+
+impl Person {
+    pub fn new(name: String, vorname: String, geburtsdatum: NaiveDate) -> Self {
+        Self {
+            name,
+            vorname,
+            geburtsname: None,
+            geburtsdatum,
+        }
+    }
+}
+
+impl std::fmt::Debug for Person {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Person")
+            .field("name", &redact::value("NAME", &self.name))
+            .field("vorname", &redact::value("VORNAME", &self.vorname))
+            .field(
+                "geburtsname",
+                &redact::opt("GEBURTSNAME", &self.geburtsname),
+            )
+            .field(
+                "geburtsdatum",
+                &redact::value("GEBURTSDATUM", &datum::format(&self.geburtsdatum)),
+            )
+            .finish()
+    }
+}
+
+impl Validate for Person {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("NAME", &self.name)?;
+        validation::required("VORNAME", &self.vorname)?;
+        validation::not_in_future("GEBURTSDATUM", &self.geburtsdatum)
+    }
+}
+
+impl WriteXml for Person {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("PERSON").write_inner_content(|w| {
+            write_field_opt(w, "NAME", Some(self.name.as_str()))?;
+            write_field_opt(w, "VORNAME", Some(self.vorname.as_str()))?;
+            write_field_opt(w, "GEBURTSNAME", self.geburtsname.as_deref())?;
+            write_field_opt(
+                w,
+                "GEBURTSDATUM",
+                Some(datum::format(&self.geburtsdatum).as_str()),
+            )?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Anschrift {
+    #[serde(rename = "STRASSE")]
+    pub strasse: String,
+    #[serde(rename = "HAUSNUMMER")]
+    pub hausnummer: String,
+    #[serde(rename = "ZUSATZ", default)]
+    pub zusatz: Option<String>,
+    #[serde(rename = "PLZ")]
+    pub plz: String,
+    #[serde(rename = "ORT")]
+    pub ort: String,
+}
+
+impl Anschrift {
+    pub fn new(strasse: String, hausnummer: String, plz: String, ort: String) -> Self {
+        Self {
+            strasse,
+            hausnummer,
+            zusatz: None,
+            plz,
+            ort,
+        }
+    }
+}
+
+impl std::fmt::Debug for Anschrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Anschrift")
+            .field("strasse", &redact::value("STRASSE", &self.strasse))
+            .field("hausnummer", &redact::value("HAUSNUMMER", &self.hausnummer))
+            .field("zusatz", &redact::opt("ZUSATZ", &self.zusatz))
+            .field("plz", &redact::value("PLZ", &self.plz))
+            .field("ort", &redact::value("ORT", &self.ort))
+            .finish()
+    }
+}
+
+impl Anschrift {
+    pub(crate) fn write_xml_as(&self, w: &mut XmlWriter, tag: &'static str) -> Result<(), Error> {
+        w.create_element(tag).write_inner_content(|w| {
+            write_field_opt(w, "STRASSE", Some(self.strasse.as_str()))?;
+            write_field_opt(w, "HAUSNUMMER", Some(self.hausnummer.as_str()))?;
+            write_field_opt(w, "ZUSATZ", self.zusatz.as_deref())?;
+            write_field_opt(w, "PLZ", Some(self.plz.as_str()))?;
+            write_field_opt(w, "ORT", Some(self.ort.as_str()))?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+impl Validate for Anschrift {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("STRASSE", &self.strasse)?;
+        validation::required("HAUSNUMMER", &self.hausnummer)?;
+        validation::plz("PLZ", &self.plz)?;
+        validation::required("ORT", &self.ort)
+    }
+}
+
+impl WriteXml for Anschrift {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        self.write_xml_as(w, "ANSCHRIFT")
+    }
+}
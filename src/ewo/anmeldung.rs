@@ -0,0 +1,446 @@
+//! EWO WOHNSITZANMELDUNG: elektronische Wohnsitzanmeldung (eWA).
+//!
+//! An Anmeldung takes several OK.KOMM calls, all with `OkKommAktion::new("EWO",
+//! "WOHNSITZANMELDUNG", <schritt>, <ziel_ags>)`. [`Wohnsitzanmeldung`] keeps
+//! the Vorgangsnummer between them and enforces their order:
+//!
+//! 1. [`Wohnsitzanmeldung::anschrift_pruefen`]: checks the person against the
+//!    current address and opens the Vorgang,
+//! 2. [`Wohnsitzanmeldung::anmelden`]: submits the new residence,
+//! 3. [`Wohnsitzanmeldung::mitziehende_anmelden`]: moves household members
+//!    along (optional),
+//! 4. [`Wohnsitzanmeldung::bestaetigung`]: finishes the Vorgang and returns
+//!    the confirmation.
+//!
+//! Every request is validated before it is sent; a failed check is returned
+//! as [`ValidationError`] and leaves the workflow unchanged.
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person, VERFAHREN};
+use crate::validation::{self, Validate, ValidationError};
+use crate::xml::{datum, ja_nein, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, AppsInfo, Error};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "WOHNSITZANMELDUNG";
+pub const ANSCHRIFT_PRUEFEN: &str = "ANSCHRIFT_PRUEFEN";
+pub const ANMELDEN: &str = "ANMELDEN";
+pub const MITZIEHENDE: &str = "MITZIEHENDE";
+pub const BESTAETIGUNG: &str = "BESTAETIGUNG";
+
+/// SUCHE of step 1: the person and the address it is registered at today.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnschriftPruefung {
+    pub person: Person,
+    pub anschrift: Anschrift,
+}
+
+impl Validate for AnschriftPruefung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.person.validate()?;
+        self.anschrift.validate()
+    }
+}
+
+impl WriteXml for AnschriftPruefung {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("WOHNSITZANMELDUNG")
+            .write_inner_content(|w| {
+                self.person.write_xml(w)?;
+                self.anschrift.write_xml(w)?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+/// Answer of step 1.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct AnschriftGeprueft {
+    #[serde(rename = "VORGANGSNUMMER")]
+    pub vorgangsnummer: String,
+    /// The person is registered at the given address.
+    #[serde(rename = "GEMELDET", deserialize_with = "ja_nein::deserialize")]
+    pub gemeldet: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wohnungsstatus {
+    Hauptwohnung,
+    Nebenwohnung,
+}
+
+impl Wohnungsstatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Wohnungsstatus::Hauptwohnung => "HAUPTWOHNUNG",
+            Wohnungsstatus::Nebenwohnung => "NEBENWOHNUNG",
+        }
+    }
+}
+
+/// DATEN of step 2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeueWohnung {
+    pub anschrift: Anschrift,
+    pub einzugsdatum: NaiveDate,
+    pub wohnungsstatus: Wohnungsstatus,
+    /// Number of the Wohnungsgeberbestätigung (§ 19 BMG).
+    pub wohnungsgeberbestaetigung: String,
+}
+
+impl Validate for NeueWohnung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.anschrift.validate()?;
+        validation::required("WOHNUNGSGEBERBESTAETIGUNG", &self.wohnungsgeberbestaetigung)
+    }
+}
+
+impl WriteXml for NeueWohnung {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("NEUE_WOHNUNG").write_inner_content(|w| {
+            self.anschrift.write_xml(w)?;
+            write_field_opt(
+                w,
+                "EINZUGSDATUM",
+                Some(datum::format(&self.einzugsdatum).as_str()),
+            )?;
+            write_field_opt(w, "WOHNUNGSSTATUS", Some(self.wohnungsstatus.as_str()))?;
+            write_field_opt(
+                w,
+                "WOHNUNGSGEBERBESTAETIGUNG",
+                Some(self.wohnungsgeberbestaetigung.as_str()),
+            )?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Beziehung {
+    Ehegatte,
+    Lebenspartner,
+    Kind,
+    Sonstige,
+}
+
+impl Beziehung {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Beziehung::Ehegatte => "EHEGATTE",
+            Beziehung::Lebenspartner => "LEBENSPARTNER",
+            Beziehung::Kind => "KIND",
+            Beziehung::Sonstige => "SONSTIGE",
+        }
+    }
+}
+
+/// Household member moving along, DATEN of step 3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mitziehender {
+    pub person: Person,
+    pub beziehung: Beziehung,
+}
+
+impl Validate for Mitziehender {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.person.validate()
+    }
+}
+
+impl WriteXml for Mitziehender {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("MITZIEHENDER").write_inner_content(|w| {
+            self.person.write_xml(w)?;
+            write_field_opt(w, "BEZIEHUNG", Some(self.beziehung.as_str()))?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+struct Mitziehende<'a>(&'a [Mitziehender]);
+
+impl WriteXml for Mitziehende<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("MITZIEHENDE")
+            .write_inner_content(|w| self.0.iter().try_for_each(|m| m.write_xml(w)))?;
+        Ok(())
+    }
+}
+
+/// SUCHE of steps 2 to 4.
+struct Vorgang<'a>(&'a str);
+
+impl WriteXml for Vorgang<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("VORGANG").write_inner_content(|w| {
+            write_field_opt(w, "VORGANGSNUMMER", Some(self.0))?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// Answer of steps 2 and 3.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct VorgangStatus {
+    #[serde(rename = "VORGANGSNUMMER")]
+    pub vorgangsnummer: String,
+    #[serde(rename = "STATUS")]
+    pub status: String,
+}
+
+/// Answer of step 4.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Anmeldebestaetigung {
+    #[serde(rename = "VORGANGSNUMMER")]
+    pub vorgangsnummer: String,
+    #[serde(rename = "AKTENZEICHEN")]
+    pub aktenzeichen: String,
+    #[serde(
+        rename = "ANMELDEDATUM",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub anmeldedatum: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Schritt {
+    Neu,
+    AnschriftGeprueft,
+    Angemeldet,
+    MitziehendeAngemeldet,
+    Abgeschlossen,
+}
+
+/// State of one Wohnsitzanmeldung, created by [`Client::wohnsitzanmeldung`].
+pub struct Wohnsitzanmeldung<'c> {
+    client: &'c Client,
+    ziel_ags: String,
+    apps_info: Option<AppsInfo>,
+    vorgangsnummer: Option<String>,
+    schritt: Schritt,
+}
+
+impl<'c> Wohnsitzanmeldung<'c> {
+    pub fn with_apps_info(mut self, apps_info: AppsInfo) -> Self {
+        self.apps_info = Some(apps_info);
+        self
+    }
+
+    pub fn schritt(&self) -> Schritt {
+        self.schritt
+    }
+
+    pub fn vorgangsnummer(&self) -> Option<&str> {
+        self.vorgangsnummer.as_deref()
+    }
+
+    fn aktion(&self, ausfuehrung: &str) -> OkKommAktion {
+        OkKommAktion::new(
+            VERFAHREN.to_owned(),
+            TYP.to_owned(),
+            ausfuehrung.to_owned(),
+            self.ziel_ags.clone(),
+        )
+    }
+
+    fn expect(&self, erlaubt: &[Schritt], ausfuehrung: &str) -> anyhow::Result<&str> {
+        if !erlaubt.contains(&self.schritt) {
+            anyhow::bail!(
+                "Wohnsitzanmeldung step {ausfuehrung} is not possible in state {:?}",
+                self.schritt
+            );
+        }
+        self.vorgangsnummer
+            .as_deref()
+            .ok_or_else(|| anyhow::Error::msg("Wohnsitzanmeldung has no Vorgangsnummer"))
+    }
+
+    /// Step 1. The workflow only moves on if the person is registered at
+    /// the given address; otherwise the step may be repeated.
+    pub async fn anschrift_pruefen(
+        &mut self,
+        pruefung: &AnschriftPruefung,
+    ) -> anyhow::Result<AnschriftGeprueft> {
+        if self.schritt != Schritt::Neu {
+            anyhow::bail!("Wohnsitzanmeldung has already been started");
+        }
+        validation::ags("AKT_ZIEL_AGS", &self.ziel_ags)?;
+        pruefung.validate()?;
+        let ergebnis: AnschriftGeprueft = self
+            .client
+            .call_uncached(
+                self.aktion(ANSCHRIFT_PRUEFEN),
+                pruefung,
+                (),
+                self.apps_info.clone(),
+            )
+            .await?
+            .into_result()?;
+        if ergebnis.gemeldet {
+            self.vorgangsnummer = Some(ergebnis.vorgangsnummer.clone());
+            self.schritt = Schritt::AnschriftGeprueft;
+        }
+        Ok(ergebnis)
+    }
+
+    /// Step 2.
+    pub async fn anmelden(&mut self, wohnung: &NeueWohnung) -> anyhow::Result<VorgangStatus> {
+        let vorgangsnummer = self.expect(&[Schritt::AnschriftGeprueft], ANMELDEN)?;
+        wohnung.validate()?;
+        let status = self
+            .client
+            .call_uncached(
+                self.aktion(ANMELDEN),
+                Vorgang(vorgangsnummer),
+                wohnung,
+                self.apps_info.clone(),
+            )
+            .await?
+            .into_result()?;
+        self.schritt = Schritt::Angemeldet;
+        Ok(status)
+    }
+
+    /// Step 3, may be left out if nobody moves along.
+    pub async fn mitziehende_anmelden(
+        &mut self,
+        mitziehende: &[Mitziehender],
+    ) -> anyhow::Result<VorgangStatus> {
+        let vorgangsnummer = self.expect(&[Schritt::Angemeldet], MITZIEHENDE)?;
+        if mitziehende.is_empty() {
+            return Err(ValidationError::Missing("MITZIEHENDER").into());
+        }
+        mitziehende.validate()?;
+        let status = self
+            .client
+            .call_uncached(
+                self.aktion(MITZIEHENDE),
+                Vorgang(vorgangsnummer),
+                Mitziehende(mitziehende),
+                self.apps_info.clone(),
+            )
+            .await?
+            .into_result()?;
+        self.schritt = Schritt::MitziehendeAngemeldet;
+        Ok(status)
+    }
+
+    /// Step 4.
+    pub async fn bestaetigung(&mut self) -> anyhow::Result<Anmeldebestaetigung> {
+        let vorgangsnummer = self.expect(
+            &[Schritt::Angemeldet, Schritt::MitziehendeAngemeldet],
+            BESTAETIGUNG,
+        )?;
+        let bestaetigung = self
+            .client
+            .call_uncached::<_, ()>(
+                self.aktion(BESTAETIGUNG),
+                Vorgang(vorgangsnummer),
+                None,
+                self.apps_info.clone(),
+            )
+            .await?
+            .into_result()?;
+        self.schritt = Schritt::Abgeschlossen;
+        Ok(bestaetigung)
+    }
+}
+
+impl Client {
+    /// Starts a Wohnsitzanmeldung at the Meldebehörde `ziel_ags`.
+    pub fn wohnsitzanmeldung(&self, ziel_ags: &str) -> Wohnsitzanmeldung<'_> {
+        Wohnsitzanmeldung {
+            client: self,
+            ziel_ags: ziel_ags.to_owned(),
+            apps_info: None,
+            vorgangsnummer: None,
+            schritt: Schritt::Neu,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{AnschriftPruefung, Beziehung, Mitziehender, NeueWohnung, Schritt, Wohnungsstatus};
+    use crate::ewo::{Anschrift, Person};
+    use crate::testing;
+    use crate::validation::ValidationError;
+
+    fn anschrift(plz: &str) -> Anschrift {
+        Anschrift {
+            plz: plz.to_owned(),
+            ..testing::anschrift()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wohnsitzanmeldung() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<VORGANG><VORGANGSNUMMER>EWA-1</VORGANGSNUMMER><GEMELDET>J</GEMELDET><STATUS>OFFEN</STATUS><AKTENZEICHEN>32-1/2025</AKTENZEICHEN></VORGANG>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_TYP>WOHNSITZANMELDUNG</AKT_TYP>"));
+                if request.contains("<AKT_AUSFUEHRUNG>ANMELDEN</AKT_AUSFUEHRUNG>") {
+                    assert!(request.contains("<SUCHE><VORGANG><VORGANGSNUMMER>EWA-1</VORGANGSNUMMER></VORGANG></SUCHE>"));
+                    assert!(request.contains("<EINZUGSDATUM>01.03.2025</EINZUGSDATUM><WOHNUNGSSTATUS>HAUPTWOHNUNG</WOHNUNGSSTATUS>"));
+                }
+            },
+        );
+        let mut anmeldung = client.wohnsitzanmeldung("05315000");
+        let wohnung = NeueWohnung {
+            anschrift: anschrift("80331"),
+            einzugsdatum: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            wohnungsstatus: Wohnungsstatus::Hauptwohnung,
+            wohnungsgeberbestaetigung: "WGB-4711".to_owned(),
+        };
+        assert!(anmeldung.anmelden(&wohnung).await.is_err());
+
+        let err = anmeldung
+            .anschrift_pruefen(&AnschriftPruefung {
+                person: testing::person(),
+                anschrift: anschrift("5114"),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ValidationError>(),
+            Some(&ValidationError::Invalid {
+                field: "PLZ",
+                reason: "expected five digits"
+            })
+        );
+        assert_eq!(anmeldung.schritt(), Schritt::Neu);
+
+        anmeldung
+            .anschrift_pruefen(&AnschriftPruefung {
+                person: testing::person(),
+                anschrift: anschrift("51147"),
+            })
+            .await?;
+        assert_eq!(anmeldung.vorgangsnummer(), Some("EWA-1"));
+        anmeldung.anmelden(&wohnung).await?;
+        anmeldung
+            .mitziehende_anmelden(&[Mitziehender {
+                person: Person {
+                    vorname: "Max".to_owned(),
+                    ..testing::person()
+                },
+                beziehung: Beziehung::Kind,
+            }])
+            .await?;
+        let bestaetigung = anmeldung.bestaetigung().await?;
+        assert_eq!(bestaetigung.aktenzeichen, "32-1/2025");
+        assert_eq!(anmeldung.schritt(), Schritt::Abgeschlossen);
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! EWO AUSKUNFT: einfache Melderegisterauskunft.
+//!
+//! A lookup is sent with `OkKommAktion::new("EWO", "AUSKUNFT", "EINFACH",
+//! <ziel_ags>)` and answers with a [`Melderegisterauskunft`]. The usual
+//! negative answers come back as FEHLER; their FEH_TYP is mapped to
+//! [`FehlerCode`] and turned into the matching result variant.
+
+use crate::ewo::{Anschrift, Person, VERFAHREN};
+use crate::redact;
+use crate::response::OkKommFehler;
+use crate::xml::{ja_nein, WriteXml, XmlWriter};
+use crate::zkoxml::{AppsInfo, Error};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "AUSKUNFT";
+pub const EINFACH: &str = "EINFACH";
+
+/// Search criteria of the lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MelderegisterSuche {
+    pub person: Person,
+    /// Last address known to the requester, narrows down ambiguous hits.
+    pub fruehere_anschrift: Option<Anschrift>,
+}
+
+impl MelderegisterSuche {
+    pub fn new(person: Person) -> Self {
+        Self {
+            person,
+            fruehere_anschrift: None,
+        }
+    }
+
+    pub fn with_fruehere_anschrift(mut self, anschrift: Anschrift) -> Self {
+        self.fruehere_anschrift = Some(anschrift);
+        self
+    }
+}
+
+impl WriteXml for MelderegisterSuche {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("MELDEREGISTERAUSKUNFT")
+            .write_inner_content(|w| {
+                self.person.write_xml(w)?;
+                if let Some(anschrift) = self.fruehere_anschrift.as_ref() {
+                    anschrift.write_xml_as(w, "FRUEHERE_ANSCHRIFT")?;
+                }
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+/// Data of a hit, as far as an einfache Melderegisterauskunft discloses it.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Auskunftsdaten {
+    #[serde(rename = "NAME")]
+    pub name: String,
+    #[serde(rename = "VORNAME")]
+    pub vorname: String,
+    #[serde(rename = "DOKTORGRAD", default)]
+    pub doktorgrad: Option<String>,
+    #[serde(rename = "ANSCHRIFT", default)]
+    pub anschrift: Option<Anschrift>,
+    #[serde(
+        rename = "VERSTORBEN",
+        default,
+        deserialize_with = "ja_nein::deserialize"
+    )]
+    pub verstorben: bool,
+}
+
+impl std::fmt::Debug for Auskunftsdaten {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Auskunftsdaten")
+            .field("name", &redact::value("NAME", &self.name))
+            .field("vorname", &redact::value("VORNAME", &self.vorname))
+            .field("doktorgrad", &self.doktorgrad)
+            .field("anschrift", &self.anschrift)
+            .field("verstorben", &self.verstorben)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Melderegisterauskunft {
+    Treffer(Auskunftsdaten),
+    KeinTreffer,
+    /// The criteria match more than one person.
+    Mehrdeutig,
+    /// The person has an Auskunftssperre; no data may be disclosed.
+    Auskunftssperre,
+}
+
+/// FEH_TYP values of the negative answers to a lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FehlerCode {
+    Auskunftssperre,
+    NichtEindeutig,
+    NichtGefunden,
+    Sonstiger(String),
+}
+
+impl From<&str> for FehlerCode {
+    fn from(value: &str) -> Self {
+        match value.trim() {
+            "AUSKUNFTSSPERRE" => Self::Auskunftssperre,
+            "NICHT_EINDEUTIG" => Self::NichtEindeutig,
+            "NICHT_GEFUNDEN" => Self::NichtGefunden,
+            other => Self::Sonstiger(other.to_owned()),
+        }
+    }
+}
+
+impl From<&OkKommFehler> for FehlerCode {
+    fn from(fehler: &OkKommFehler) -> Self {
+        Self::from(fehler.typ.as_str())
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Auskunft {
+    #[serde(rename = "ANZAHL_TREFFER", default)]
+    anzahl_treffer: Option<usize>,
+    #[serde(rename = "PERSON", default)]
+    personen: Vec<Auskunftsdaten>,
+}
+
+impl From<Auskunft> for Melderegisterauskunft {
+    fn from(mut auskunft: Auskunft) -> Self {
+        let anzahl = auskunft.anzahl_treffer.unwrap_or(auskunft.personen.len());
+        if anzahl > 1 || auskunft.personen.len() > 1 {
+            return Self::Mehrdeutig;
+        }
+        match auskunft.personen.pop() {
+            Some(person) if anzahl == 1 => Self::Treffer(person),
+            _ => Self::KeinTreffer,
+        }
+    }
+}
+
+impl Client {
+    /// Einfache Melderegisterauskunft at the Meldebehörde `ziel_ags`.
+    ///
+    /// FEHLER other than the ones in [`FehlerCode`] are returned as
+    /// [`OkKommFehler`].
+    pub async fn melderegisterauskunft(
+        &self,
+        ziel_ags: &str,
+        suche: &MelderegisterSuche,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<Melderegisterauskunft> {
+        let aktion = OkKommAktion::new(
+            VERFAHREN.to_owned(),
+            TYP.to_owned(),
+            EINFACH.to_owned(),
+            ziel_ags.to_owned(),
+        );
+        let response = self.call(aktion, suche, (), apps_info).await?;
+        if let Some(err) = response.error() {
+            let fehler = OkKommFehler::from(err);
+            return match FehlerCode::from(&fehler) {
+                FehlerCode::Auskunftssperre => Ok(Melderegisterauskunft::Auskunftssperre),
+                FehlerCode::NichtEindeutig => Ok(Melderegisterauskunft::Mehrdeutig),
+                FehlerCode::NichtGefunden => Ok(Melderegisterauskunft::KeinTreffer),
+                FehlerCode::Sonstiger(_) => Err(fehler.into()),
+            };
+        }
+        Ok(response.deserialize::<Auskunft>()?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{MelderegisterSuche, Melderegisterauskunft};
+    use crate::response::OkKommFehler;
+    use crate::testing;
+
+    fn suche() -> MelderegisterSuche {
+        MelderegisterSuche::new(testing::person())
+    }
+
+    async fn auskunft(antwort: &str, daten: &str) -> anyhow::Result<Melderegisterauskunft> {
+        testing::client(testing::zkocxml(antwort, daten), |request| {
+            assert!(request
+                .contains("<AKT_TYP>AUSKUNFT</AKT_TYP><AKT_AUSFUEHRUNG>EINFACH</AKT_AUSFUEHRUNG>"));
+            assert!(
+                request.contains("<SUCHE><MELDEREGISTERAUSKUNFT><PERSON><NAME>Mustermann</NAME>")
+            );
+        })
+        .melderegisterauskunft("09162000", &suche(), None)
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_melderegisterauskunft() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match auskunft(
+            "",
+            "<AUSKUNFT><ANZAHL_TREFFER>1</ANZAHL_TREFFER><PERSON><NAME>Mustermann</NAME><VORNAME>Erika</VORNAME><ANSCHRIFT><STRASSE>Heidestraße</STRASSE><HAUSNUMMER>17</HAUSNUMMER><PLZ>51147</PLZ><ORT>Köln</ORT></ANSCHRIFT></PERSON></AUSKUNFT>",
+        )
+        .await?
+        {
+            Melderegisterauskunft::Treffer(person) => {
+                assert_eq!(person.vorname, "Erika");
+                assert_eq!(person.anschrift.unwrap().ort, "Köln");
+                assert!(!person.verstorben);
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+        assert_eq!(
+            auskunft(
+                "",
+                "<AUSKUNFT><ANZAHL_TREFFER>3</ANZAHL_TREFFER></AUSKUNFT>"
+            )
+            .await?,
+            Melderegisterauskunft::Mehrdeutig
+        );
+        assert_eq!(
+            auskunft(
+                "",
+                "<AUSKUNFT><ANZAHL_TREFFER>0</ANZAHL_TREFFER></AUSKUNFT>"
+            )
+            .await?,
+            Melderegisterauskunft::KeinTreffer
+        );
+        assert_eq!(
+            auskunft(&testing::fehler("AUSKUNFTSSPERRE", "Auskunftssperre"), "").await?,
+            Melderegisterauskunft::Auskunftssperre
+        );
+        assert_eq!(
+            auskunft(
+                &testing::fehler("NICHT_GEFUNDEN", "Person nicht gefunden"),
+                ""
+            )
+            .await?,
+            Melderegisterauskunft::KeinTreffer
+        );
+        let err = auskunft(&testing::fehler("F", "Systemfehler"), "")
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<OkKommFehler>().unwrap().typ, "F");
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! EWO MELDEBESCHEINIGUNG: Meldebescheinigung as PDF.
+//!
+//! Requested with `OkKommAktion::new("EWO", "MELDEBESCHEINIGUNG",
+//! "ERSTELLEN", <ziel_ags>)`. The answer carries an
+//! `OK_KOMM_CONTENTCONTAINER` in DATEN: a `text/xml` message with the
+//! Meldedaten and the document as attachment.
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person, VERFAHREN};
+use crate::redact;
+use crate::response::OkKommFehler;
+use crate::xml::{datum, WriteXml, XmlWriter};
+use crate::zkoxml::{
+    write_field_opt, AppsInfo, ContentContainerAttachment, ContentContainerResponse, Error,
+};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "MELDEBESCHEINIGUNG";
+pub const ERSTELLEN: &str = "ERSTELLEN";
+pub const PDF: &str = "application/pdf";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Bescheinigungsart {
+    /// Name, Doktorgrad and current address only.
+    #[default]
+    Einfach,
+    /// Including further Meldedaten like Staatsangehörigkeit and
+    /// Familienstand.
+    Erweitert,
+}
+
+impl Bescheinigungsart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bescheinigungsart::Einfach => "EINFACH",
+            Bescheinigungsart::Erweitert => "ERWEITERT",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeldebescheinigungAntrag {
+    pub art: Bescheinigungsart,
+    pub person: Person,
+    pub anschrift: Anschrift,
+}
+
+impl MeldebescheinigungAntrag {
+    pub fn new(art: Bescheinigungsart, person: Person, anschrift: Anschrift) -> Self {
+        Self {
+            art,
+            person,
+            anschrift,
+        }
+    }
+}
+
+impl WriteXml for MeldebescheinigungAntrag {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("MELDEBESCHEINIGUNG")
+            .write_inner_content(|w| {
+                write_field_opt(w, "ART", Some(self.art.as_str()))?;
+                self.person.write_xml(w)?;
+                self.anschrift.write_xml(w)?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+/// Meldedaten as printed on the Meldebescheinigung.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Meldedaten {
+    #[serde(rename = "PERSON")]
+    pub person: Person,
+    #[serde(rename = "ANSCHRIFT")]
+    pub anschrift: Anschrift,
+    #[serde(
+        rename = "EINZUGSDATUM",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub einzugsdatum: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meldebescheinigung {
+    pub meldedaten: Meldedaten,
+    /// The document, usually with content type [`PDF`].
+    pub dokument: ContentContainerAttachment,
+}
+
+impl Meldebescheinigung {
+    pub fn from_content_container(container: ContentContainerResponse) -> anyhow::Result<Self> {
+        let message = container
+            .messages
+            .iter()
+            .find(|message| message.content_type == "text/xml")
+            .ok_or_else(|| anyhow::Error::msg("Meldebescheinigung without Meldedaten"))?;
+        let meldedaten = quick_xml::de::from_str(&message.content).map_err(|err| {
+            anyhow::Error::msg(redact::text(&format!(
+                "Meldedaten cannot be parsed: {err:#?}"
+            )))
+        })?;
+        let dokument = container
+            .attachment(PDF)
+            .or(container.attachments.first())
+            .cloned()
+            .ok_or_else(|| anyhow::Error::msg("Meldebescheinigung without document"))?;
+        Ok(Self {
+            meldedaten,
+            dokument,
+        })
+    }
+}
+
+impl Client {
+    pub async fn meldebescheinigung(
+        &self,
+        ziel_ags: &str,
+        antrag: &MeldebescheinigungAntrag,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<Meldebescheinigung> {
+        let aktion = OkKommAktion::new(
+            VERFAHREN.to_owned(),
+            TYP.to_owned(),
+            ERSTELLEN.to_owned(),
+            ziel_ags.to_owned(),
+        );
+        let response = self.call_uncached(aktion, antrag, (), apps_info).await?;
+        if let Some(err) = response.error() {
+            return Err(OkKommFehler::from(err).into());
+        }
+        let container = response
+            .daten
+            .as_deref()
+            .map(ContentContainerResponse::parse)
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| anyhow::Error::msg("OK.KOMM answer contains no content container"))?;
+        Meldebescheinigung::from_content_container(container)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use chrono::NaiveDate;
+
+    use super::{Bescheinigungsart, Meldebescheinigung, MeldebescheinigungAntrag, PDF};
+    use crate::testing;
+    use crate::zkoxml::{ContentContainerMessage, ContentContainerResponse};
+
+    #[tokio::test]
+    async fn test_meldebescheinigung() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let meldedaten = "<MELDEDATEN><PERSON><NAME>Mustermann</NAME><VORNAME>Erika</VORNAME><GEBURTSDATUM>12.08.1964</GEBURTSDATUM></PERSON><ANSCHRIFT><STRASSE>Heidestraße</STRASSE><HAUSNUMMER>17</HAUSNUMMER><PLZ>51147</PLZ><ORT>Köln</ORT></ANSCHRIFT><EINZUGSDATUM>01.04.2020</EINZUGSDATUM></MELDEDATEN>";
+        let daten = format!(
+            r#"<OK_KOMM_CONTENTCONTAINER><MESSAGES type="include"><MESSAGE contentType="text/xml" refId="daten"><OK_KOMM_RAW_BASE64><![CDATA[{}]]></OK_KOMM_RAW_BASE64></MESSAGE></MESSAGES><ATTACHMENTS type="include"><ATTACHMENT contentType="application/pdf" refId="meldebescheinigung.pdf"><OK_KOMM_RAW_BASE64><![CDATA[{}]]></OK_KOMM_RAW_BASE64></ATTACHMENT></ATTACHMENTS></OK_KOMM_CONTENTCONTAINER>"#,
+            STANDARD.encode(meldedaten),
+            STANDARD.encode(b"%PDF-1.7"),
+        );
+        let client = testing::client(testing::zkocxml("", &daten), |request| {
+            assert!(request.contains("<AKT_TYP>MELDEBESCHEINIGUNG</AKT_TYP>"));
+            assert!(request.contains("<SUCHE><MELDEBESCHEINIGUNG><ART>ERWEITERT</ART><PERSON>"));
+        });
+        let bescheinigung = client
+            .meldebescheinigung(
+                "05315000",
+                &MeldebescheinigungAntrag::new(
+                    Bescheinigungsart::Erweitert,
+                    testing::person(),
+                    testing::anschrift(),
+                ),
+                None,
+            )
+            .await?;
+        assert_eq!(bescheinigung.meldedaten.person.vorname, "Erika");
+        assert_eq!(
+            bescheinigung.meldedaten.einzugsdatum,
+            NaiveDate::from_ymd_opt(2020, 4, 1)
+        );
+        assert_eq!(bescheinigung.dokument.content_type, PDF);
+        assert_eq!(bescheinigung.dokument.ref_id, "meldebescheinigung.pdf");
+        assert_eq!(&bescheinigung.dokument.content[..], b"%PDF-1.7");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_meldedaten() {
+        let container = ContentContainerResponse {
+            messages: vec![ContentContainerMessage {
+                content_type: "text/xml".to_owned(),
+                ref_id: "daten".to_owned(),
+                content: "<MELDEDATEN><PERSON><NAME>Mustermann</NAME></PERSON><ANSCHRIFT><STRASSE>Heidestraße</STRASSE></MELDEDATEN>".to_owned(),
+            }],
+            attachments: Vec::new(),
+        };
+        let err = Meldebescheinigung::from_content_container(container).unwrap_err();
+        assert!(err.to_string().starts_with("Meldedaten cannot be parsed"));
+        assert!(!format!("{err:?}").contains("Mustermann"));
+        assert!(!format!("{err:?}").contains("Heidestraße"));
+    }
+}
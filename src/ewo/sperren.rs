@@ -0,0 +1,273 @@
+//! EWO UEBERMITTLUNGSSPERREN: Widersprüche against data transfers.
+//!
+//! Citizens may object to certain transfers of their Meldedaten (BMG §§ 36,
+//! 42 and 50). [`ABRUFEN`] returns the Sperren currently set; [`AENDERN`]
+//! sets or revokes Sperren and answers with the resulting set. Both use
+//! `OkKommAktion::new("EWO", "UEBERMITTLUNGSSPERREN", <schritt>, <ziel_ags>)`.
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person, VERFAHREN};
+use crate::validation::{self, Validate, ValidationError};
+use crate::xml::{datum, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, AppsInfo, Error};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "UEBERMITTLUNGSSPERREN";
+pub const ABRUFEN: &str = "ABRUFEN";
+pub const AENDERN: &str = "AENDERN";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(from = "String")]
+pub enum Sperrart {
+    /// Parteien and Wählergruppen before elections (§ 50 Abs. 1 BMG).
+    Parteien,
+    /// Alters- and Ehejubiläen to Mandatsträger and press (§ 50 Abs. 2 BMG).
+    Jubilaeen,
+    /// Adressbuchverlage (§ 50 Abs. 3 BMG).
+    Adressbuch,
+    /// Family members' data to öffentlich-rechtliche Religionsgesellschaften
+    /// (§ 42 Abs. 3 BMG).
+    Religionsgesellschaft,
+    /// Bundesamt für das Personalmanagement der Bundeswehr (§ 36 Abs. 2 BMG).
+    Bundeswehr,
+    Unbekannt(String),
+}
+
+impl Sperrart {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Sperrart::Parteien => "PARTEIEN",
+            Sperrart::Jubilaeen => "JUBILAEEN",
+            Sperrart::Adressbuch => "ADRESSBUCH",
+            Sperrart::Religionsgesellschaft => "RELIGIONSGESELLSCHAFT",
+            Sperrart::Bundeswehr => "BUNDESWEHR",
+            Sperrart::Unbekannt(value) => value,
+        }
+    }
+}
+
+impl From<String> for Sperrart {
+    fn from(value: String) -> Self {
+        match value.trim() {
+            "PARTEIEN" => Self::Parteien,
+            "JUBILAEEN" => Self::Jubilaeen,
+            "ADRESSBUCH" => Self::Adressbuch,
+            "RELIGIONSGESELLSCHAFT" => Self::Religionsgesellschaft,
+            "BUNDESWEHR" => Self::Bundeswehr,
+            _ => Self::Unbekannt(value),
+        }
+    }
+}
+
+/// SUCHE of both steps: the person whose Sperren are managed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SperrenSuche {
+    pub person: Person,
+    pub anschrift: Anschrift,
+}
+
+impl Validate for SperrenSuche {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.person.validate()?;
+        self.anschrift.validate()
+    }
+}
+
+impl WriteXml for SperrenSuche {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element(TYP).write_inner_content(|w| {
+            self.person.write_xml(w)?;
+            self.anschrift.write_xml(w)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// DATEN of [`AENDERN`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SperrenAenderung {
+    pub setzen: Vec<Sperrart>,
+    pub aufheben: Vec<Sperrart>,
+}
+
+impl SperrenAenderung {
+    pub fn setzen(mut self, sperrart: Sperrart) -> Self {
+        self.setzen.push(sperrart);
+        self
+    }
+
+    pub fn aufheben(mut self, sperrart: Sperrart) -> Self {
+        self.aufheben.push(sperrart);
+        self
+    }
+}
+
+impl Validate for SperrenAenderung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.setzen.is_empty() && self.aufheben.is_empty() {
+            return Err(ValidationError::Missing("SPERRART"));
+        }
+        if self
+            .setzen
+            .iter()
+            .chain(&self.aufheben)
+            .any(|s| matches!(s, Sperrart::Unbekannt(_)))
+        {
+            return Err(validation::invalid("SPERRART", "unknown Sperrart"));
+        }
+        if self.setzen.iter().any(|s| self.aufheben.contains(s)) {
+            return Err(validation::invalid(
+                "SPERRART",
+                "Sperrart is both set and revoked",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl WriteXml for SperrenAenderung {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        fn write_list(
+            w: &mut XmlWriter,
+            tag: &'static str,
+            list: &[Sperrart],
+        ) -> Result<(), Error> {
+            if list.is_empty() {
+                return Ok(());
+            }
+            w.create_element(tag).write_inner_content(|w| {
+                list.iter()
+                    .try_for_each(|s| write_field_opt(w, "SPERRART", Some(s.as_str())))
+            })?;
+            Ok(())
+        }
+
+        w.create_element("SPERREN_AENDERUNG")
+            .write_inner_content(|w| {
+                write_list(w, "SETZEN", &self.setzen)?;
+                write_list(w, "AUFHEBEN", &self.aufheben)
+            })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Sperre {
+    #[serde(rename = "SPERRART")]
+    pub sperrart: Sperrart,
+    #[serde(
+        rename = "SEIT",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub seit: Option<NaiveDate>,
+}
+
+/// Answer of both steps: all Sperren active now.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct AktiveSperren {
+    #[serde(rename = "SPERRE", default)]
+    pub sperren: Vec<Sperre>,
+}
+
+impl AktiveSperren {
+    pub fn ist_gesetzt(&self, sperrart: &Sperrart) -> bool {
+        self.sperren.iter().any(|s| &s.sperrart == sperrart)
+    }
+}
+
+fn aktion(ausfuehrung: &str, ziel_ags: &str) -> OkKommAktion {
+    OkKommAktion::new(
+        VERFAHREN.to_owned(),
+        TYP.to_owned(),
+        ausfuehrung.to_owned(),
+        ziel_ags.to_owned(),
+    )
+}
+
+impl Client {
+    /// Current Übermittlungssperren of a person. Never answered from the
+    /// [`cache`](crate::cache), so a change by
+    /// [`Client::uebermittlungssperren_aendern`] is visible immediately.
+    pub async fn uebermittlungssperren(
+        &self,
+        ziel_ags: &str,
+        suche: &SperrenSuche,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<AktiveSperren> {
+        suche.validate()?;
+        self.call_uncached(aktion(ABRUFEN, ziel_ags), suche, (), apps_info)
+            .await?
+            .into_result()
+    }
+
+    pub async fn uebermittlungssperren_aendern(
+        &self,
+        ziel_ags: &str,
+        suche: &SperrenSuche,
+        aenderung: &SperrenAenderung,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<AktiveSperren> {
+        suche.validate()?;
+        aenderung.validate()?;
+        self.call_uncached(aktion(AENDERN, ziel_ags), suche, aenderung, apps_info)
+            .await?
+            .into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{Sperrart, SperrenAenderung, SperrenSuche};
+    use crate::testing;
+
+    #[tokio::test]
+    async fn test_uebermittlungssperren_aendern(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<UEBERMITTLUNGSSPERREN><SPERRE><SPERRART>PARTEIEN</SPERRART><SEIT>03.02.2025</SEIT></SPERRE><SPERRE><SPERRART>ADRESSBUCH</SPERRART></SPERRE></UEBERMITTLUNGSSPERREN>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_TYP>UEBERMITTLUNGSSPERREN</AKT_TYP><AKT_AUSFUEHRUNG>AENDERN</AKT_AUSFUEHRUNG>"));
+                assert!(request.contains("<DATEN><SPERREN_AENDERUNG><SETZEN><SPERRART>PARTEIEN</SPERRART></SETZEN><AUFHEBEN><SPERRART>JUBILAEEN</SPERRART></AUFHEBEN></SPERREN_AENDERUNG></DATEN>"));
+            },
+        );
+        let suche = SperrenSuche {
+            person: testing::person(),
+            anschrift: testing::anschrift(),
+        };
+        let sperren = client
+            .uebermittlungssperren_aendern(
+                "05315000",
+                &suche,
+                &SperrenAenderung::default()
+                    .setzen(Sperrart::Parteien)
+                    .aufheben(Sperrart::Jubilaeen),
+                None,
+            )
+            .await?;
+        assert!(sperren.ist_gesetzt(&Sperrart::Parteien));
+        assert!(sperren.ist_gesetzt(&Sperrart::Adressbuch));
+        assert!(!sperren.ist_gesetzt(&Sperrart::Jubilaeen));
+        assert_eq!(sperren.sperren[0].seit, NaiveDate::from_ymd_opt(2025, 2, 3));
+
+        assert!(client
+            .uebermittlungssperren_aendern(
+                "05315000",
+                &suche,
+                &SperrenAenderung::default()
+                    .setzen(Sperrart::Bundeswehr)
+                    .aufheben(Sperrart::Bundeswehr),
+                None,
+            )
+            .await
+            .is_err());
+        Ok(())
+    }
+}
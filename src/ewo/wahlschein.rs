@@ -0,0 +1,396 @@
+//! EWO WEBWAHLSCHEIN: Wahlschein and Briefwahl applications.
+//!
+//! The flow has three steps, all routed with `OkKommAktion::new("EWO",
+//! "WEBWAHLSCHEIN", <ausfuehrung>, <ziel_ags>)`:
+//!
+//! 1. [`ABRUFEN`]: look up the voter register entry ([`WahlscheinSuche`] →
+//!    [`WahlscheinAuskunft`]),
+//! 2. [`BEANTRAGEN`]: apply for the Wahlschein ([`WahlscheinSuche`] plus
+//!    [`WahlscheinAntrag`] as DATEN → [`WahlscheinQuittung`]),
+//! 3. [`STATUS`]: query the state of an application
+//!    ([`WahlscheinStatusAnfrage`] → [`WahlscheinQuittung`]).
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person, VERFAHREN};
+use crate::redact;
+use crate::validation::{self, Validate, ValidationError};
+use crate::xml::{datum, ja_nein, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, AppsInfo, Error};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "WEBWAHLSCHEIN";
+pub const ABRUFEN: &str = "ABRUFEN";
+pub const BEANTRAGEN: &str = "BEANTRAGEN";
+pub const STATUS: &str = "STATUS";
+
+pub fn aktion(ausfuehrung: &str, ziel_ags: &str) -> OkKommAktion {
+    OkKommAktion::new(
+        VERFAHREN.to_owned(),
+        TYP.to_owned(),
+        ausfuehrung.to_owned(),
+        ziel_ags.to_owned(),
+    )
+}
+
+/// Identifies the voter, either by Wählerverzeichnis number from the
+/// Wahlbenachrichtigung or by person and address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WahlscheinSuche {
+    pub wahl_id: Option<String>,
+    pub wahlbezirk: Option<String>,
+    pub waehlerverzeichnis_nr: Option<String>,
+    pub person: Person,
+    pub anschrift: Anschrift,
+}
+
+impl WahlscheinSuche {
+    pub fn new(person: Person, anschrift: Anschrift) -> Self {
+        Self {
+            wahl_id: None,
+            wahlbezirk: None,
+            waehlerverzeichnis_nr: None,
+            person,
+            anschrift,
+        }
+    }
+
+    pub fn with_wahl_id<S: ToString>(mut self, wahl_id: S) -> Self {
+        self.wahl_id = Some(wahl_id.to_string());
+        self
+    }
+
+    pub fn with_wahlbezirk<S: ToString>(mut self, wahlbezirk: S) -> Self {
+        self.wahlbezirk = Some(wahlbezirk.to_string());
+        self
+    }
+
+    pub fn with_waehlerverzeichnis_nr<S: ToString>(mut self, waehlerverzeichnis_nr: S) -> Self {
+        self.waehlerverzeichnis_nr = Some(waehlerverzeichnis_nr.to_string());
+        self
+    }
+}
+
+impl Validate for WahlscheinSuche {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.person.validate()?;
+        self.anschrift.validate()
+    }
+}
+
+impl WriteXml for WahlscheinSuche {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("WAHLSCHEIN").write_inner_content(|w| {
+            write_field_opt(w, "WAHL_ID", self.wahl_id.as_deref())?;
+            write_field_opt(w, "WAHLBEZIRK", self.wahlbezirk.as_deref())?;
+            write_field_opt(
+                w,
+                "WAEHLERVERZEICHNIS_NR",
+                self.waehlerverzeichnis_nr.as_deref(),
+            )?;
+            self.person.write_xml(w)?;
+            self.anschrift.write_xml(w)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(from = "String")]
+pub enum WahlscheinStatus {
+    /// No application has been made yet.
+    NichtBeantragt,
+    Beantragt,
+    InBearbeitung,
+    Versendet,
+    Abgeholt,
+    Abgelehnt,
+    Unbekannt(String),
+}
+
+impl From<String> for WahlscheinStatus {
+    fn from(value: String) -> Self {
+        match value.trim() {
+            "" | "NICHT_BEANTRAGT" => Self::NichtBeantragt,
+            "BEANTRAGT" => Self::Beantragt,
+            "IN_BEARBEITUNG" => Self::InBearbeitung,
+            "VERSENDET" => Self::Versendet,
+            "ABGEHOLT" => Self::Abgeholt,
+            "ABGELEHNT" => Self::Abgelehnt,
+            _ => Self::Unbekannt(value),
+        }
+    }
+}
+
+/// Answer to [`ABRUFEN`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct WahlscheinAuskunft {
+    #[serde(rename = "WAHLBERECHTIGT", deserialize_with = "ja_nein::deserialize")]
+    pub wahlberechtigt: bool,
+    #[serde(rename = "WAHL_ID", default)]
+    pub wahl_id: Option<String>,
+    #[serde(rename = "WAHLBEZIRK", default)]
+    pub wahlbezirk: Option<String>,
+    #[serde(rename = "WAEHLERVERZEICHNIS_NR", default)]
+    pub waehlerverzeichnis_nr: Option<String>,
+    #[serde(rename = "STATUS")]
+    pub status: WahlscheinStatus,
+    #[serde(rename = "ANTRAGSNUMMER", default)]
+    pub antragsnummer: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Versandart {
+    /// Sent to the registered address.
+    Post,
+    /// Sent to [`WahlscheinAntrag::versandanschrift`].
+    AbweichendeAnschrift,
+    /// Picked up at the Wahlamt.
+    Abholung,
+}
+
+impl Versandart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Versandart::Post => "POST",
+            Versandart::AbweichendeAnschrift => "ABWEICHENDE_ANSCHRIFT",
+            Versandart::Abholung => "ABHOLUNG",
+        }
+    }
+}
+
+/// DATEN of [`BEANTRAGEN`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct WahlscheinAntrag {
+    pub briefwahlunterlagen: bool,
+    pub versandart: Versandart,
+    pub versandanschrift: Option<Anschrift>,
+    pub email: Option<String>,
+}
+
+impl WahlscheinAntrag {
+    pub fn new(versandart: Versandart) -> Self {
+        Self {
+            briefwahlunterlagen: true,
+            versandart,
+            versandanschrift: None,
+            email: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for WahlscheinAntrag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WahlscheinAntrag")
+            .field("briefwahlunterlagen", &self.briefwahlunterlagen)
+            .field("versandart", &self.versandart)
+            .field("versandanschrift", &self.versandanschrift)
+            .field("email", &redact::opt("EMAIL", &self.email))
+            .finish()
+    }
+}
+
+impl Validate for WahlscheinAntrag {
+    fn validate(&self) -> Result<(), ValidationError> {
+        match (self.versandart, self.versandanschrift.as_ref()) {
+            (Versandart::AbweichendeAnschrift, None) => {
+                Err(ValidationError::Missing("VERSANDANSCHRIFT"))
+            }
+            (_, Some(anschrift)) => anschrift.validate(),
+            (_, None) => Ok(()),
+        }
+    }
+}
+
+impl WriteXml for WahlscheinAntrag {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("ANTRAG").write_inner_content(|w| {
+            write_field_opt(
+                w,
+                "BRIEFWAHLUNTERLAGEN",
+                Some(ja_nein::format(self.briefwahlunterlagen)),
+            )?;
+            write_field_opt(w, "VERSANDART", Some(self.versandart.as_str()))?;
+            if let Some(anschrift) = self.versandanschrift.as_ref() {
+                anschrift.write_xml_as(w, "VERSANDANSCHRIFT")?;
+            }
+            write_field_opt(w, "EMAIL", self.email.as_deref())?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// SUCHE of [`STATUS`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct WahlscheinStatusAnfrage {
+    pub antragsnummer: String,
+    pub geburtsdatum: NaiveDate,
+}
+
+impl std::fmt::Debug for WahlscheinStatusAnfrage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WahlscheinStatusAnfrage")
+            .field("antragsnummer", &self.antragsnummer)
+            .field(
+                "geburtsdatum",
+                &redact::value("GEBURTSDATUM", &datum::format(&self.geburtsdatum)),
+            )
+            .finish()
+    }
+}
+
+impl Validate for WahlscheinStatusAnfrage {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("ANTRAGSNUMMER", &self.antragsnummer)
+    }
+}
+
+impl WriteXml for WahlscheinStatusAnfrage {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("WAHLSCHEIN_STATUS")
+            .write_inner_content(|w| {
+                write_field_opt(w, "ANTRAGSNUMMER", Some(self.antragsnummer.as_str()))?;
+                write_field_opt(
+                    w,
+                    "GEBURTSDATUM",
+                    Some(datum::format(&self.geburtsdatum).as_str()),
+                )?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+/// Answer to [`BEANTRAGEN`] and [`STATUS`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct WahlscheinQuittung {
+    #[serde(rename = "ANTRAGSNUMMER")]
+    pub antragsnummer: String,
+    #[serde(rename = "STATUS")]
+    pub status: WahlscheinStatus,
+    #[serde(
+        rename = "EINGANGSDATUM",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub eingangsdatum: Option<NaiveDate>,
+    #[serde(
+        rename = "VERSANDDATUM",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub versanddatum: Option<NaiveDate>,
+}
+
+impl Client {
+    pub async fn wahlschein_abrufen(
+        &self,
+        ziel_ags: &str,
+        suche: &WahlscheinSuche,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<WahlscheinAuskunft> {
+        suche.validate()?;
+        self.call(aktion(ABRUFEN, ziel_ags), suche, (), apps_info)
+            .await?
+            .into_result()
+    }
+
+    pub async fn wahlschein_beantragen(
+        &self,
+        ziel_ags: &str,
+        suche: &WahlscheinSuche,
+        antrag: &WahlscheinAntrag,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<WahlscheinQuittung> {
+        suche.validate()?;
+        antrag.validate()?;
+        self.call_uncached(aktion(BEANTRAGEN, ziel_ags), suche, antrag, apps_info)
+            .await?
+            .into_result()
+    }
+
+    pub async fn wahlschein_status(
+        &self,
+        ziel_ags: &str,
+        anfrage: &WahlscheinStatusAnfrage,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<WahlscheinQuittung> {
+        anfrage.validate()?;
+        self.call(aktion(STATUS, ziel_ags), anfrage, (), apps_info)
+            .await?
+            .into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{Versandart, WahlscheinAntrag, WahlscheinStatus, WahlscheinSuche};
+    use crate::response::OkKommFehler;
+    use crate::testing;
+    use crate::validation::ValidationError;
+
+    fn suche() -> WahlscheinSuche {
+        WahlscheinSuche::new(testing::person(), testing::anschrift())
+            .with_waehlerverzeichnis_nr("0815")
+    }
+
+    #[tokio::test]
+    async fn test_wahlschein_beantragen() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<WAHLSCHEIN><ANTRAGSNUMMER>WS-2025-42</ANTRAGSNUMMER><STATUS>BEANTRAGT</STATUS><EINGANGSDATUM>03.02.2025</EINGANGSDATUM></WAHLSCHEIN>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_VERFAHREN>EWO</AKT_VERFAHREN><AKT_TYP>WEBWAHLSCHEIN</AKT_TYP><AKT_AUSFUEHRUNG>BEANTRAGEN</AKT_AUSFUEHRUNG><AKT_ZIEL_AGS>05315000</AKT_ZIEL_AGS>"));
+                assert!(request.contains("<SUCHE><WAHLSCHEIN><WAEHLERVERZEICHNIS_NR>0815</WAEHLERVERZEICHNIS_NR><PERSON><NAME>Mustermann</NAME><VORNAME>Erika</VORNAME><GEBURTSDATUM>12.08.1964</GEBURTSDATUM></PERSON>"));
+                assert!(request.contains("<DATEN><ANTRAG><BRIEFWAHLUNTERLAGEN>J</BRIEFWAHLUNTERLAGEN><VERSANDART>POST</VERSANDART></ANTRAG></DATEN>"));
+            },
+        );
+        let quittung = client
+            .wahlschein_beantragen(
+                "05315000",
+                &suche(),
+                &WahlscheinAntrag::new(Versandart::Post),
+                None,
+            )
+            .await?;
+        assert_eq!(quittung.antragsnummer, "WS-2025-42");
+        assert_eq!(quittung.status, WahlscheinStatus::Beantragt);
+        assert_eq!(quittung.eingangsdatum, NaiveDate::from_ymd_opt(2025, 2, 3));
+        assert_eq!(quittung.versanddatum, None);
+
+        let err = client
+            .wahlschein_beantragen(
+                "05315000",
+                &suche(),
+                &WahlscheinAntrag::new(Versandart::AbweichendeAnschrift),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ValidationError>(),
+            Some(&ValidationError::Missing("VERSANDANSCHRIFT"))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wahlschein_abrufen_fehler() {
+        let client = testing::client(
+            testing::zkocxml(&testing::fehler("F", "Person nicht gefunden"), ""),
+            |_| {},
+        );
+        let err = client
+            .wahlschein_abrufen("05315000", &suche(), None)
+            .await
+            .unwrap_err();
+        let fehler = err.downcast_ref::<OkKommFehler>().unwrap();
+        assert_eq!(fehler.text, "Person nicht gefunden");
+    }
+}
@@ -0,0 +1,5 @@
+//! Typed requests for the GEWERBE Verfahren (Gewerbeanzeigen).
+
+pub mod anzeige;
+
+pub const VERFAHREN: &str = "GEWERBE";
@@ -0,0 +1,410 @@
+//! GEWERBE GEWERBEANZEIGE: Gewerbeanmeldung, -ummeldung and -abmeldung.
+//!
+//! Sent with `OkKommAktion::new("GEWERBE", "GEWERBEANZEIGE", <art>,
+//! <ziel_ags>)`, where `<art>` is [`ANMELDUNG`], [`UMMELDUNG`] or
+//! [`ABMELDUNG`]. SUCHE names the kind of Anzeige and, for Um- and
+//! Abmeldung, the Aktenzeichen of the Gewerbe; the Anzeige itself goes into
+//! XML_DATEN (see [`Gewerbeanzeige::to_request`]). The Gewerbeamt answers
+//! with a [`GewerbeQuittung`].
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person};
+use crate::gewerbe::VERFAHREN;
+use crate::validation::{self, Validate, ValidationError};
+use crate::xml::{datum, ja_nein, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, AppsInfo, Error, Request};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "GEWERBEANZEIGE";
+pub const ANMELDUNG: &str = "ANMELDUNG";
+pub const UMMELDUNG: &str = "UMMELDUNG";
+pub const ABMELDUNG: &str = "ABMELDUNG";
+
+/// The person running the Gewerbe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inhaber {
+    pub person: Person,
+    pub anschrift: Anschrift,
+    pub staatsangehoerigkeit: Option<String>,
+}
+
+impl Inhaber {
+    pub fn new(person: Person, anschrift: Anschrift) -> Self {
+        Self {
+            person,
+            anschrift,
+            staatsangehoerigkeit: None,
+        }
+    }
+}
+
+impl Validate for Inhaber {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.person.validate()?;
+        self.anschrift.validate()
+    }
+}
+
+impl WriteXml for Inhaber {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("INHABER").write_inner_content(|w| {
+            self.person.write_xml(w)?;
+            self.anschrift.write_xml(w)?;
+            write_field_opt(
+                w,
+                "STAATSANGEHOERIGKEIT",
+                self.staatsangehoerigkeit.as_deref(),
+            )?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Betrieb {
+    /// Registered name, if the business has one.
+    pub firma: Option<String>,
+    pub rechtsform: Option<String>,
+    /// Description of the angezeigte Tätigkeit.
+    pub taetigkeit: String,
+    pub betriebsstaette: Anschrift,
+}
+
+impl Betrieb {
+    pub fn new(taetigkeit: String, betriebsstaette: Anschrift) -> Self {
+        Self {
+            firma: None,
+            rechtsform: None,
+            taetigkeit,
+            betriebsstaette,
+        }
+    }
+}
+
+impl Validate for Betrieb {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("TAETIGKEIT", &self.taetigkeit)?;
+        self.betriebsstaette.validate()
+    }
+}
+
+impl WriteXml for Betrieb {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("BETRIEB").write_inner_content(|w| {
+            write_field_opt(w, "FIRMA", self.firma.as_deref())?;
+            write_field_opt(w, "RECHTSFORM", self.rechtsform.as_deref())?;
+            write_field_opt(w, "TAETIGKEIT", Some(self.taetigkeit.as_str()))?;
+            self.betriebsstaette.write_xml_as(w, "BETRIEBSSTAETTE")?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// Common part of the three kinds of Anzeige.
+pub trait Gewerbeanzeige: WriteXml + Validate {
+    /// [`ANMELDUNG`], [`UMMELDUNG`] or [`ABMELDUNG`].
+    const ART: &'static str;
+
+    /// Aktenzeichen of the registered Gewerbe, `None` for an Anmeldung.
+    fn aktenzeichen(&self) -> Option<&str>;
+
+    fn suche(&self) -> AnzeigeSuche<'_> {
+        AnzeigeSuche {
+            art: Self::ART,
+            aktenzeichen: self.aktenzeichen(),
+        }
+    }
+
+    /// ZKOCXML request with [`Gewerbeanzeige::suche`] as SUCHE and the
+    /// Anzeige as XML_DATEN.
+    fn to_request(
+        &self,
+        ziel_ags: &str,
+        apps_info: Option<AppsInfo>,
+    ) -> Request<AnzeigeSuche<'_>, &Self>
+    where
+        Self: Sized,
+    {
+        Request::new(self.suche(), apps_info)
+            .with_verfahren(VERFAHREN)
+            .with_typ(TYP)
+            .with_ausfuehrung(Self::ART)
+            .with_ziel_ags(ziel_ags)
+            .with_xml_daten(self)
+    }
+}
+
+/// SUCHE of a Gewerbeanzeige.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnzeigeSuche<'a> {
+    pub art: &'static str,
+    pub aktenzeichen: Option<&'a str>,
+}
+
+impl WriteXml for AnzeigeSuche<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element(TYP).write_inner_content(|w| {
+            write_field_opt(w, "ANZEIGEART", Some(self.art))?;
+            write_field_opt(w, "AKTENZEICHEN", self.aktenzeichen)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gewerbeanmeldung {
+    pub inhaber: Inhaber,
+    pub betrieb: Betrieb,
+    pub beginn: NaiveDate,
+    pub nebenerwerb: bool,
+    pub beschaeftigte: Option<u32>,
+}
+
+impl Validate for Gewerbeanmeldung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.inhaber.validate()?;
+        self.betrieb.validate()
+    }
+}
+
+impl WriteXml for Gewerbeanmeldung {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("GEWERBEANMELDUNG")
+            .write_inner_content(|w| {
+                self.inhaber.write_xml(w)?;
+                self.betrieb.write_xml(w)?;
+                write_field_opt(w, "BEGINN", Some(datum::format(&self.beginn).as_str()))?;
+                write_field_opt(w, "NEBENERWERB", Some(ja_nein::format(self.nebenerwerb)))?;
+                write_field_opt(
+                    w,
+                    "BESCHAEFTIGTE",
+                    self.beschaeftigte.map(|b| b.to_string()).as_deref(),
+                )?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+impl Gewerbeanzeige for Gewerbeanmeldung {
+    const ART: &'static str = ANMELDUNG;
+
+    fn aktenzeichen(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Change of Tätigkeit, Betriebsstätte or Inhaber data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gewerbeummeldung {
+    pub aktenzeichen: String,
+    pub inhaber: Inhaber,
+    /// The Betrieb as it is after the change.
+    pub betrieb: Betrieb,
+    pub datum: NaiveDate,
+    pub grund: Option<String>,
+}
+
+impl Validate for Gewerbeummeldung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("AKTENZEICHEN", &self.aktenzeichen)?;
+        self.inhaber.validate()?;
+        self.betrieb.validate()
+    }
+}
+
+impl WriteXml for Gewerbeummeldung {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("GEWERBEUMMELDUNG")
+            .write_inner_content(|w| {
+                self.inhaber.write_xml(w)?;
+                self.betrieb.write_xml(w)?;
+                write_field_opt(w, "DATUM", Some(datum::format(&self.datum).as_str()))?;
+                write_field_opt(w, "GRUND", self.grund.as_deref())?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+impl Gewerbeanzeige for Gewerbeummeldung {
+    const ART: &'static str = UMMELDUNG;
+
+    fn aktenzeichen(&self) -> Option<&str> {
+        Some(&self.aktenzeichen)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abmeldegrund {
+    Aufgabe,
+    Verlegung,
+    Uebergabe,
+    Sonstiger,
+}
+
+impl Abmeldegrund {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Abmeldegrund::Aufgabe => "AUFGABE",
+            Abmeldegrund::Verlegung => "VERLEGUNG",
+            Abmeldegrund::Uebergabe => "UEBERGABE",
+            Abmeldegrund::Sonstiger => "SONSTIGER",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gewerbeabmeldung {
+    pub aktenzeichen: String,
+    pub inhaber: Inhaber,
+    pub aufgabe: NaiveDate,
+    pub grund: Abmeldegrund,
+}
+
+impl Validate for Gewerbeabmeldung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("AKTENZEICHEN", &self.aktenzeichen)?;
+        self.inhaber.validate()
+    }
+}
+
+impl WriteXml for Gewerbeabmeldung {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("GEWERBEABMELDUNG")
+            .write_inner_content(|w| {
+                self.inhaber.write_xml(w)?;
+                write_field_opt(w, "AUFGABE", Some(datum::format(&self.aufgabe).as_str()))?;
+                write_field_opt(w, "GRUND", Some(self.grund.as_str()))?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+impl Gewerbeanzeige for Gewerbeabmeldung {
+    const ART: &'static str = ABMELDUNG;
+
+    fn aktenzeichen(&self) -> Option<&str> {
+        Some(&self.aktenzeichen)
+    }
+}
+
+/// Receipt of the Gewerbeamt.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct GewerbeQuittung {
+    #[serde(rename = "AKTENZEICHEN")]
+    pub aktenzeichen: String,
+    #[serde(
+        rename = "EINGANGSDATUM",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub eingangsdatum: Option<NaiveDate>,
+    #[serde(rename = "STATUS", default)]
+    pub status: Option<String>,
+}
+
+impl Client {
+    /// Validates `anzeige` and sends it to the Gewerbeamt `ziel_ags`.
+    pub async fn gewerbeanzeige<A>(
+        &self,
+        ziel_ags: &str,
+        anzeige: &A,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<GewerbeQuittung>
+    where
+        A: Gewerbeanzeige,
+    {
+        validation::ags("AKT_ZIEL_AGS", ziel_ags)?;
+        anzeige.validate()?;
+        let aktion = OkKommAktion::new(
+            VERFAHREN.to_owned(),
+            TYP.to_owned(),
+            A::ART.to_owned(),
+            ziel_ags.to_owned(),
+        );
+        self.call_uncached(aktion, anzeige.suche(), anzeige, apps_info)
+            .await?
+            .into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{
+        Abmeldegrund, Betrieb, Gewerbeabmeldung, Gewerbeanmeldung, Gewerbeanzeige, Inhaber,
+    };
+    use crate::ewo::Anschrift;
+    use crate::testing;
+    use crate::validation::ValidationError;
+
+    fn inhaber() -> Inhaber {
+        Inhaber::new(testing::person(), testing::anschrift())
+    }
+
+    #[tokio::test]
+    async fn test_gewerbeanzeige() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<QUITTUNG><AKTENZEICHEN>GEW-2025-0815</AKTENZEICHEN><EINGANGSDATUM>03.02.2025</EINGANGSDATUM></QUITTUNG>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_VERFAHREN>GEWERBE</AKT_VERFAHREN><AKT_TYP>GEWERBEANZEIGE</AKT_TYP><AKT_AUSFUEHRUNG>ANMELDUNG</AKT_AUSFUEHRUNG>"));
+                assert!(request.contains("<SUCHE><GEWERBEANZEIGE><ANZEIGEART>ANMELDUNG</ANZEIGEART></GEWERBEANZEIGE></SUCHE>"));
+                assert!(request.contains("<XML_DATEN><DATEN><GEWERBEANMELDUNG><INHABER><PERSON>"));
+                assert!(request.contains("<TAETIGKEIT>Softwareentwicklung</TAETIGKEIT><BETRIEBSSTAETTE><STRASSE>"));
+                assert!(request.contains("<BEGINN>01.03.2025</BEGINN><NEBENERWERB>N</NEBENERWERB></GEWERBEANMELDUNG>"));
+            },
+        );
+        let anmeldung = Gewerbeanmeldung {
+            inhaber: inhaber(),
+            betrieb: Betrieb::new(
+                "Softwareentwicklung".to_owned(),
+                Anschrift::new(
+                    "Hohe Straße".to_owned(),
+                    "1".to_owned(),
+                    "50667".to_owned(),
+                    "Köln".to_owned(),
+                ),
+            ),
+            beginn: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            nebenerwerb: false,
+            beschaeftigte: None,
+        };
+        let quittung = client.gewerbeanzeige("05315000", &anmeldung, None).await?;
+        assert_eq!(quittung.aktenzeichen, "GEW-2025-0815");
+        assert_eq!(quittung.eingangsdatum, NaiveDate::from_ymd_opt(2025, 2, 3));
+
+        let request = String::from_utf8(
+            anmeldung
+                .to_request("05315000", None)
+                .to_message()?
+                .to_vec(),
+        )?;
+        assert!(request.contains("<XML_DATEN><DATEN><GEWERBEANMELDUNG>"));
+
+        let abmeldung = Gewerbeabmeldung {
+            aktenzeichen: " ".to_owned(),
+            inhaber: inhaber(),
+            aufgabe: NaiveDate::from_ymd_opt(2025, 6, 30).unwrap(),
+            grund: Abmeldegrund::Aufgabe,
+        };
+        let err = client
+            .gewerbeanzeige("05315000", &abmeldung, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ValidationError>(),
+            Some(&ValidationError::Missing("AKTENZEICHEN"))
+        );
+        Ok(())
+    }
+}
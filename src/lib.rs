@@ -1,30 +1,69 @@
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::Error;
-use bytes::{BufMut, BytesMut};
-use quick_xml::Writer;
-use reqwest::{header, Response};
 use serde::Deserialize;
 
 use soap::SoapRequest;
 use zkoxml::ContentContainerAttachment;
 
+use crate::audit::{Audit, AuditRecord, AuditSink, AuditStatus, AuditSuche};
+use crate::breaker::Breaker;
+use crate::cache::ResponseCache;
+use crate::config::Defaults;
+use crate::limits::Limiter;
+use crate::mandanten::MandantenCache;
 use crate::okkomm::{OkKommCallApplicationByte, OkKommCallApplicationByteResponse};
+use crate::response::OkKommResponse;
 use crate::soap::SoapResponse;
+use crate::tls::ClientIdentity;
+use crate::transport::{ReqwestTransport, Transport, TransportResponse};
 use crate::xml::WriteXml;
-use crate::zkoxml::{AppsInfo, ContentContainer, ContentContainerMessage, RawBase64, Request};
+use crate::zkoxml::{
+    AppsInfo, ContentContainer, ContentContainerMessage, RawBase64, Request, ZkocxmlInfo,
+};
 
+pub mod audit;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod breaker;
+pub mod cache;
+pub mod config;
+pub mod ewo;
+pub mod gewerbe;
+pub mod limits;
+pub mod mandanten;
 pub mod okkomm;
+pub mod pass;
+pub mod redact;
+pub mod replay;
+pub mod response;
+pub mod routing;
+#[cfg(feature = "tower")]
+pub mod service;
 pub mod soap;
+pub mod steuer;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod tls;
+pub mod transport;
+pub mod validation;
+pub mod verkehr;
 pub mod xml;
 pub mod zkoxml;
 
 #[derive(Clone)]
 pub struct Client {
-    client: reqwest::Client,
-    pub url: String,
+    transport: Arc<dyn Transport>,
+    audit: Option<Audit>,
+    mandanten_cache: Option<Arc<MandantenCache>>,
+    defaults: Defaults,
+    limiter: Option<Arc<Limiter>>,
+    breaker: Option<Arc<Breaker>>,
+    response_cache: Option<Arc<ResponseCache>>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct OkKommAktion {
     pub verfahren: String,
     pub typ: String,
@@ -43,33 +82,196 @@ impl OkKommAktion {
     }
 }
 
+pub(crate) fn soap_body<R, D>(
+    defaults: &Defaults,
+    info: OkKommAktion,
+    request: impl Into<Option<R>>,
+    data: impl Into<Option<D>>,
+    apps_info: Option<AppsInfo>,
+) -> Result<SoapRequest<OkKommCallApplicationByte<bytes::Bytes>>, quick_xml::Error>
+where
+    R: WriteXml,
+    D: WriteXml,
+{
+    let mut zkoxml_request = Request::new(request, defaults.apps_info(apps_info))
+        .with_verfahren(info.verfahren)
+        .with_typ(info.typ)
+        .with_ausfuehrung(info.ausfuehrung)
+        .with_ziel_ags(info.ziel_ags)
+        .with_xml_daten(data);
+    if let Some((techuser, techpwd)) = defaults.login.as_ref() {
+        zkoxml_request = zkoxml_request.with_login(techuser, techpwd);
+    }
+    let zkoxml_body = zkoxml_request.to_message()?;
+    Ok(SoapRequest::new(OkKommCallApplicationByte::new(
+        zkoxml_body,
+    )))
+}
+
+pub(crate) fn soap_body_base64<T>(
+    defaults: &Defaults,
+    info: OkKommAktion,
+    body: T,
+    apps_info: Option<AppsInfo>,
+) -> Result<SoapRequest<OkKommCallApplicationByte<bytes::Bytes>>, quick_xml::Error>
+where
+    T: WriteXml,
+{
+    soap_body(
+        defaults,
+        info,
+        RawBase64 {
+            body: String::from_utf8_lossy(&xml::to_bytes(&body)?).to_string(),
+        },
+        (),
+        apps_info,
+    )
+}
+
+pub(crate) fn soap_body_content_container<T>(
+    defaults: &Defaults,
+    info: OkKommAktion,
+    body: T,
+    attachments: Vec<ContentContainerAttachment>,
+    ref_id: String,
+    apps_info: Option<AppsInfo>,
+) -> Result<SoapRequest<OkKommCallApplicationByte<bytes::Bytes>>, quick_xml::Error>
+where
+    T: WriteXml,
+{
+    soap_body(
+        defaults,
+        info,
+        ContentContainer {
+            messages: &vec![ContentContainerMessage {
+                content_type: "text/xml".to_string(),
+                ref_id,
+                content: String::from_utf8_lossy(&xml::to_bytes(&body)?).to_string(),
+            }],
+            attachments: &attachments,
+        },
+        (),
+        apps_info,
+    )
+}
+
+pub(crate) fn decode_response_text(
+    response_text: &str,
+) -> anyhow::Result<(Option<ZkocxmlInfo>, Option<String>)> {
+    match SoapResponse::<OkKommCallApplicationByteResponse>::from_str(response_text) {
+        Ok(soap_response) => match soap_response.into_inner() {
+            Some(soap_xml) => match soap_xml.decode() {
+                Ok(decoded) => Ok(decoded),
+                Err(err) => Err(Error::msg(redact::text(&format!(
+                    "Error while parsing OK.KOMM response: {err:#?}"
+                )))),
+            },
+            None => Err(Error::msg(redact::text(&format!(
+                "SOAP response from OK.KOMM cannot be parsed: {response_text:#?}"
+            )))),
+        },
+        Err(err) => Err(Error::msg(redact::text(&format!(
+            "Error while parsing SOAP response from OK.KOMM: {err:#?}"
+        )))),
+    }
+}
+
+pub(crate) fn decode_transport_response(
+    result: anyhow::Result<TransportResponse>,
+) -> anyhow::Result<(Option<ZkocxmlInfo>, Option<String>)> {
+    let response = result?;
+    match decode_response_text(&String::from_utf8_lossy(&response.body)) {
+        Ok(decoded) => Ok(decoded),
+        Err(err) if !response.is_success() => Err(err.context(format!(
+            "OK.KOMM responded with HTTP status {}",
+            response.status
+        ))),
+        Err(err) => Err(err),
+    }
+}
+
+pub(crate) fn handle_decoded<R>(
+    audit: Option<&Audit>,
+    audit_record: Option<AuditRecord>,
+    decoded: anyhow::Result<(Option<ZkocxmlInfo>, Option<String>)>,
+) -> anyhow::Result<R>
+where
+    R: for<'a> Deserialize<'a>,
+{
+    finish_audit(audit, audit_record, &decoded);
+    let (info, xml) = decoded?;
+    parse_daten(info.as_ref(), xml.as_deref())
+}
+
+pub(crate) fn finish_audit<T>(
+    audit: Option<&Audit>,
+    audit_record: Option<AuditRecord>,
+    decoded: &anyhow::Result<(Option<ZkocxmlInfo>, T)>,
+) {
+    if let (Some(audit), Some(record)) = (audit, audit_record) {
+        audit.finish(record, AuditStatus::from_decoded(decoded));
+    }
+}
+
+pub(crate) fn parse_daten<R>(info: Option<&ZkocxmlInfo>, xml: Option<&str>) -> anyhow::Result<R>
+where
+    R: for<'a> Deserialize<'a>,
+{
+    match xml {
+        Some(xml) => match quick_xml::de::from_str::<R>(xml) {
+            Ok(result) => Ok(result),
+            Err(err) => Err(Error::msg(redact::text(&format!(
+                "Error while deserializing OK.KOMM result: {err:#?}"
+            )))),
+        },
+        None => Err(Error::msg(redact::text(&format!(
+            "OK.KOMM result cannot be parsed: {xml:#?} / {info:#?}"
+        )))),
+    }
+}
+
 impl Client {
     pub fn new(url: String, tls_root_certificates: Option<Vec<Vec<u8>>>) -> anyhow::Result<Self> {
-        let mut headers = header::HeaderMap::new();
-        headers.insert(
-            "Content-Type",
-            header::HeaderValue::from_static("text/xml; charset=utf-8"),
-        );
-
-        let mut client_builder = reqwest::ClientBuilder::new()
-            .no_proxy()
-            .default_headers(headers);
-
-        if let Some(certs) = tls_root_certificates {
-            for cert in certs {
-                match reqwest::Certificate::from_pem(&cert) {
-                    Ok(cert) => {
-                        client_builder = client_builder.add_root_certificate(cert);
-                    }
-                    Err(err) => log::error!("Error while parsing certificate: {err:#?}"),
-                }
-            }
-        }
+        Self::new_with_identity(url, tls_root_certificates, None)
+    }
 
-        Ok(Self {
-            client: client_builder.build()?,
+    pub fn new_with_identity(
+        url: String,
+        tls_root_certificates: Option<Vec<Vec<u8>>>,
+        identity: Option<ClientIdentity>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self::from_transport(ReqwestTransport::new(
             url,
-        })
+            tls_root_certificates,
+            identity,
+        )?))
+    }
+
+    pub fn from_transport(transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            audit: None,
+            mandanten_cache: None,
+            defaults: Defaults::default(),
+            limiter: None,
+            breaker: None,
+            response_cache: None,
+        }
+    }
+
+    /// Hands an [`AuditRecord`] for every `send_*` call to `sink`.
+    pub fn with_audit_sink(mut self, sink: impl AuditSink + 'static, suche: AuditSuche) -> Self {
+        self.audit = Some(Audit::new(Arc::new(sink), suche));
+        self
+    }
+
+    pub fn transport(&self) -> &Arc<dyn Transport> {
+        &self.transport
+    }
+
+    /// URL of the endpoint, `None` for transports that do not post to one.
+    pub fn url(&self) -> Option<&str> {
+        self.transport.url()
     }
 
     pub fn soap_body<R, D>(
@@ -83,137 +285,160 @@ impl Client {
         R: WriteXml,
         D: WriteXml,
     {
-        let zkoxml_body = Request::new(request, apps_info)
-            .with_verfahren(info.verfahren)
-            .with_typ(info.typ)
-            .with_ausfuehrung(info.ausfuehrung)
-            .with_ziel_ags(info.ziel_ags)
-            .with_xml_daten(data)
-            .to_message()?;
-        Ok(SoapRequest::new(OkKommCallApplicationByte::new(
-            zkoxml_body,
-        )))
+        soap_body(&self.defaults, info, request, data, apps_info)
     }
 
-    pub fn request_soap<T>(
+    /// Sends a prepared envelope. Only the endpoint wide [`limits`]
+    /// and [`breaker`] apply, as the target AGS is not known here.
+    pub async fn send_soap<T>(
         &self,
         soap_request: SoapRequest<T>,
-    ) -> Result<reqwest::RequestBuilder, quick_xml::Error>
+    ) -> anyhow::Result<TransportResponse>
+    where
+        T: WriteXml,
+    {
+        self.send_soap_to(None, soap_request).await
+    }
+
+    async fn send_soap_to<T>(
+        &self,
+        ziel_ags: Option<&str>,
+        soap_request: SoapRequest<T>,
+    ) -> anyhow::Result<TransportResponse>
     where
         T: WriteXml,
     {
         let body = soap_request.to_message()?;
-        Ok(self.client.post(&self.url).body(body))
+        let breaker = match self.breaker.as_ref() {
+            Some(breaker) => Some(breaker.check(ziel_ags)?),
+            None => None,
+        };
+        let _permit = match self.limiter.as_ref() {
+            Some(limiter) => limiter.acquire(ziel_ags).await?,
+            None => None,
+        };
+        let result = self.transport.send(body).await;
+        if let Some(breaker) = breaker {
+            breaker.record(matches!(&result, Ok(response) if response.status < 500));
+        }
+        result
     }
 
-    pub fn request<T>(
+    pub async fn send_request<T>(
         &self,
         info: OkKommAktion,
         body: T,
         apps_info: Option<AppsInfo>,
-    ) -> Result<reqwest::RequestBuilder, quick_xml::Error>
+    ) -> anyhow::Result<TransportResponse>
     where
         T: WriteXml,
     {
+        let ziel_ags = info.ziel_ags.clone();
         let soap_request = self.soap_body(info, body, (), apps_info)?;
-        self.request_soap(soap_request)
+        self.send_soap_to(Some(&ziel_ags), soap_request).await
     }
 
-    fn request_xml_base64<T>(
+    fn begin_audit<T>(
         &self,
-        info: OkKommAktion,
-        body: T,
-        apps_info: Option<AppsInfo>,
-    ) -> Result<reqwest::RequestBuilder, quick_xml::Error>
+        info: &OkKommAktion,
+        apps_info: Option<&AppsInfo>,
+        body: &T,
+    ) -> Option<AuditRecord>
     where
         T: WriteXml,
     {
-        let write_buf = BytesMut::new();
-        let mut writer = Writer::new(write_buf.writer());
-        body.write_xml(&mut writer)?;
-
-        self.request(
-            info,
-            RawBase64 {
-                body: String::from_utf8_lossy(&writer.into_inner().into_inner().freeze())
-                    .to_string(),
-            },
-            apps_info,
-        )
+        self.audit
+            .as_ref()
+            .map(|audit| audit.begin(info, apps_info, body))
     }
 
-    fn request_xml_in_content_container<T>(
+    /// Sends `request` as SUCHE and `data` as DATEN and returns the decoded
+    /// answer, FEHLER included. Answers to requests without DATEN may come
+    /// from the [`cache`].
+    pub async fn call<R, D>(
         &self,
         info: OkKommAktion,
-        body: T,
-        attachments: Vec<ContentContainerAttachment>,
-        ref_id: String,
+        request: R,
+        data: impl Into<Option<D>>,
         apps_info: Option<AppsInfo>,
-    ) -> Result<reqwest::RequestBuilder, quick_xml::Error>
+    ) -> anyhow::Result<OkKommResponse>
     where
-        T: WriteXml,
+        R: WriteXml,
+        D: WriteXml,
     {
-        let write_buf = BytesMut::new();
-        let mut writer = Writer::new(write_buf.writer());
-        body.write_xml(&mut writer)?;
+        self.call_cached(info, request, data.into(), apps_info, true)
+            .await
+    }
 
-        self.request(
-            info,
-            ContentContainer {
-                messages: &vec![ContentContainerMessage {
-                    content_type: "text/xml".to_string(),
-                    ref_id,
-                    content: String::from_utf8_lossy(&writer.into_inner().into_inner().freeze())
-                        .to_string(),
-                }],
-                attachments: &attachments,
-            },
-            apps_info,
-        )
+    /// Like [`Client::call`], but never uses the [`cache`]; for requests
+    /// that change data.
+    pub(crate) async fn call_uncached<R, D>(
+        &self,
+        info: OkKommAktion,
+        request: R,
+        data: impl Into<Option<D>>,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<OkKommResponse>
+    where
+        R: WriteXml,
+        D: WriteXml,
+    {
+        self.call_cached(info, request, data.into(), apps_info, false)
+            .await
     }
 
-    async fn handle_request_result<R>(result: Result<Response, reqwest::Error>) -> anyhow::Result<R>
+    async fn call_cached<R, D>(
+        &self,
+        info: OkKommAktion,
+        request: R,
+        data: Option<D>,
+        apps_info: Option<AppsInfo>,
+        cache: bool,
+    ) -> anyhow::Result<OkKommResponse>
     where
-        R: for<'a> Deserialize<'a>,
+        R: WriteXml,
+        D: WriteXml,
     {
-        match result {
-            Ok(response) => match response.text().await {
-                Ok(response_text) => {
-                    match SoapResponse::<OkKommCallApplicationByteResponse>::from_str(
-                        response_text.as_str(),
-                    ) {
-                        Ok(soap_response) => match soap_response.into_inner() {
-                            Some(soap_xml) => match soap_xml.decode() {
-                                Ok((info, xml)) => match xml {
-                                    Some(xml) => match quick_xml::de::from_str::<R>(xml.as_str()) {
-                                        Ok(result) => Ok(result),
-                                        Err(err) => Err(Error::from(err)),
-                                    },
-                                    None => Err(Error::msg(format!(
-                                        "OK.KOMM result cannot be parsed: {xml:#?} / {info:#?}"
-                                    ))),
-                                },
-                                Err(err) => Err(Error::msg(format!(
-                                    "Error while parsing OK.KOMM response: {err:#?}"
-                                ))),
-                            },
-                            None => Err(Error::msg(format!(
-                                "SOAP response from OK.KOMM cannot be parsed: {response_text:#?}"
-                            ))),
-                        },
-                        Err(err) => Err(Error::msg(format!(
-                            "Error while parsing SOAP response from OK.KOMM: {err:#?}"
-                        ))),
-                    }
-                }
-                Err(err) => Err(Error::msg(format!(
-                    "Error while receiving SOAP response from OK.KOMM: {err:#?}"
-                ))),
-            },
-            Err(err) => Err(Error::msg(format!(
-                "Error while sending SOAP request to OK.KOMM: {err:#?}"
-            ))),
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &request);
+        let cache_entry = match self.response_cache.as_ref().filter(|_| cache) {
+            Some(cache) => cache.entry(
+                &info,
+                self.defaults
+                    .login
+                    .as_ref()
+                    .map(|(techuser, _)| techuser.as_str()),
+                apps_info.as_ref(),
+                &request,
+                data.as_ref(),
+            ),
+            None => None,
+        };
+        let cached = match cache_entry.as_ref() {
+            Some(entry) => entry.get().await,
+            None => None,
+        };
+        let cache_entry = cache_entry.filter(|_| cached.is_none());
+        let result = match cached {
+            Some(response) => Ok(response),
+            None => {
+                let ziel_ags = info.ziel_ags.clone();
+                let soap_request = self.soap_body::<R, D>(info, request, data, apps_info)?;
+                self.send_soap_to(Some(&ziel_ags), soap_request).await
+            }
+        };
+        let body = match &result {
+            Ok(response) if response.is_success() => Some(response.body.clone()),
+            _ => None,
+        };
+        let decoded = decode_transport_response(result);
+        finish_audit(self.audit.as_ref(), audit_record, &decoded);
+        let (info, daten) = decoded?;
+        let response = OkKommResponse { info, daten };
+        if let (Some(entry), Some(body)) = (cache_entry, body) {
+            entry.put(body, &response).await;
         }
+        Ok(response)
     }
 
     pub async fn send_request_xml<T, R>(
@@ -226,8 +451,14 @@ impl Client {
         T: WriteXml,
         R: for<'a> Deserialize<'a>,
     {
-        let result = self.request(info, body, apps_info)?.send().await;
-        Self::handle_request_result(result).await
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &body);
+        let result = self.send_request(info, body, apps_info).await;
+        handle_decoded(
+            self.audit.as_ref(),
+            audit_record,
+            decode_transport_response(result),
+        )
     }
 
     pub async fn send_request_xml_base64<T, R>(
@@ -240,8 +471,16 @@ impl Client {
         T: WriteXml,
         R: for<'a> Deserialize<'a>,
     {
-        let result = self.request_xml_base64(info, body, apps_info)?.send().await;
-        Self::handle_request_result(result).await
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &body);
+        let ziel_ags = info.ziel_ags.clone();
+        let soap_request = soap_body_base64(&self.defaults, info, body, apps_info)?;
+        let result = self.send_soap_to(Some(&ziel_ags), soap_request).await;
+        handle_decoded(
+            self.audit.as_ref(),
+            audit_record,
+            decode_transport_response(result),
+        )
     }
 
     pub async fn send_request_xml_in_content_container<T, R>(
@@ -256,25 +495,36 @@ impl Client {
         T: WriteXml,
         R: for<'a> Deserialize<'a>,
     {
-        let result = self
-            .request_xml_in_content_container(info, body, attachments, ref_id, apps_info)?
-            .send()
-            .await;
-        Self::handle_request_result(result).await
+        let apps_info = self.defaults.apps_info(apps_info);
+        let audit_record = self.begin_audit(&info, apps_info.as_ref(), &body);
+        let ziel_ags = info.ziel_ags.clone();
+        let soap_request = soap_body_content_container(
+            &self.defaults,
+            info,
+            body,
+            attachments,
+            ref_id,
+            apps_info,
+        )?;
+        let result = self.send_soap_to(Some(&ziel_ags), soap_request).await;
+        handle_decoded(
+            self.audit.as_ref(),
+            audit_record,
+            decode_transport_response(result),
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
-
-    use soap::SoapResponse;
     use zkoxml::Request;
 
-    use crate::okkomm::{OkKommCallApplicationByte, OkKommCallApplicationByteResponse};
+    use crate::okkomm::{response_envelope, OkKommCallApplicationByte};
     use crate::soap::SoapRequest;
+    use crate::transport::{MemoryTransport, TransportResponse};
+    use crate::zkoxml;
     use crate::zkoxml::RawRequest;
-    use crate::{soap, zkoxml, Client, OkKommAktion};
+    use crate::{Client, OkKommAktion};
 
     #[test]
     fn test_to_message_soap_envelope() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -287,6 +537,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_url() -> anyhow::Result<()> {
+        let client = Client::new("https://okkomm.example/komm".to_owned(), None)?;
+        assert_eq!(client.url(), Some("https://okkomm.example/komm"));
+        let client = Client::from_transport(MemoryTransport::new(|_| {
+            Ok(TransportResponse::new(200, ""))
+        }));
+        assert_eq!(client.url(), None);
+        Ok(())
+    }
+
     // #[tokio::test]
     // async fn client_test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     //     let client = Client::new(
@@ -295,7 +556,7 @@ mod tests {
     //     )?;
     //     let raw_request = RawRequest("<MANDANTENANFRAGE></MANDANTENANFRAGE>".to_owned());
     //     let res = client
-    //         .request(
+    //         .send_request(
     //             OkKommAktion::new(
     //                 "EWO".to_owned(),
     //                 "WEBWAHLSCHEIN".to_owned(),
@@ -304,10 +565,9 @@ mod tests {
     //             ),
     //             raw_request,
     //             None,
-    //         )?
-    //         .send()
+    //         )
     //         .await?;
-    //     let body = res.text().await?;
+    //     let body = String::from_utf8_lossy(&res.body);
     //     let res = SoapResponse::<OkKommCallApplicationByteResponse>::from_str(&body)?;
     //     if let Some(res) = res.into_inner() {
     //         let (info, data) = res.decode()?;
@@ -317,6 +577,41 @@ mod tests {
     //     Ok(())
     // }
 
+    #[tokio::test]
+    async fn test_client_with_memory_transport(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        struct Mandant {
+            #[serde(rename = "AGS")]
+            ags: String,
+        }
+
+        let client = Client::from_transport(MemoryTransport::new(|request| {
+            let request = String::from_utf8_lossy(&request).to_string();
+            assert!(request.contains("okk:xmlParameter"));
+            Ok(TransportResponse::new(
+                200,
+                response_envelope(
+                    b"<ZKOCXML><XML_SYSTEM><SYSTEM/></XML_SYSTEM><XML_DATEN><DATEN><MANDANT><AGS>09000011</AGS></MANDANT></DATEN></XML_DATEN></ZKOCXML>",
+                ),
+            ))
+        }));
+        let mandant: Mandant = client
+            .send_request_xml(
+                OkKommAktion::new(
+                    "EWO".to_owned(),
+                    "WEBWAHLSCHEIN".to_owned(),
+                    "ABRUFEN".to_owned(),
+                    "09000011".to_owned(),
+                ),
+                RawRequest("<MANDANTENANFRAGE></MANDANTENANFRAGE>".to_owned()),
+                None,
+            )
+            .await?;
+        assert_eq!(mandant.ags, "09000011");
+        Ok(())
+    }
+
     #[test]
     fn test_to_message_zkocxml() -> Result<(), Box<dyn std::error::Error>> {
         let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><ZKOCXML><XML_SYSTEM><SYSTEM><AKTION><AKT_VERFAHREN></AKT_VERFAHREN><AKT_TYP></AKT_TYP><AKT_AUSFUEHRUNG></AKT_AUSFUEHRUNG><AKT_ZIEL_AGS></AKT_ZIEL_AGS></AKTION><AKT_LOGIN><AKT_TECHUSER></AKT_TECHUSER><AKT_TECHPWD></AKT_TECHPWD></AKT_LOGIN><ANTWORT></ANTWORT><APPS_INFO><APPS_TYP>DGS</APPS_TYP><APPS_NAME>Digital Gov as a Service</APPS_NAME><APPS_VERSION></APPS_VERSION><APPS_AGS></APPS_AGS><APPS_DATUM>24.01.2023</APPS_DATUM><APPS_UHRZEIT>12:17:37</APPS_UHRZEIT><APPS_REQUEST_ID></APPS_REQUEST_ID><APPS_SOURCE_ID></APPS_SOURCE_ID><APPS_KENNUNG></APPS_KENNUNG><APPS_IP_ADRESSE></APPS_IP_ADRESSE><APPS_ZIEL_URL></APPS_ZIEL_URL><APPS_RETURN_QUEUE></APPS_RETURN_QUEUE></APPS_INFO></SYSTEM></XML_SYSTEM><XML_PROFIL><SUCHE>test</SUCHE></XML_PROFIL></ZKOCXML>"#;
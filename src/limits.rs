@@ -0,0 +1,401 @@
+//! Concurrency and rate limits of a [`Client`].
+//!
+//! Small OK.KOMM instances cannot take many requests at once. [`Limits`]
+//! caps the requests in flight and applies token buckets per endpoint (i.e.
+//! per `Client`, use a [`RoutingClient`](crate::routing::RoutingClient) for
+//! several endpoints) and per target AGS. Requests over the limit wait until
+//! they may be sent; if that takes longer than [`Limits::queue_timeout`],
+//! they fail with [`LimitError::QueueTimeout`].
+//!
+//! The limits apply to the `send_*` and `call` methods of the async
+//! [`Client`], not to the blocking client or to `Client::service`; the
+//! tower service can be limited with tower's own layers.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::Client;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LimitError {
+    #[error("OK.KOMM request could not be sent within the queue timeout of {0:?}")]
+    QueueTimeout(Duration),
+    #[error("invalid limit: {0}")]
+    Invalid(&'static str),
+}
+
+pub(crate) fn secs<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    f64::deserialize(deserializer)
+        .and_then(|secs| Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom))
+}
+
+/// `requests` per `per`, with bursts of up to `requests`. Both have to be
+/// positive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RateFields")]
+pub struct Rate {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RateFields {
+    requests: u32,
+    #[serde(deserialize_with = "secs")]
+    per_secs: Duration,
+}
+
+impl TryFrom<RateFields> for Rate {
+    type Error = LimitError;
+
+    fn try_from(fields: RateFields) -> Result<Self, Self::Error> {
+        let rate = Rate::new(fields.requests, fields.per_secs);
+        rate.validate()?;
+        Ok(rate)
+    }
+}
+
+impl Rate {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self { requests, per }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    fn validate(&self) -> Result<(), LimitError> {
+        if self.requests == 0 {
+            return Err(LimitError::Invalid("rate requests must be positive"));
+        }
+        if self.per.is_zero() {
+            return Err(LimitError::Invalid("rate period must be positive"));
+        }
+        Ok(())
+    }
+
+    fn tokens_per_sec(&self) -> f64 {
+        f64::from(self.requests) / self.per.as_secs_f64()
+    }
+}
+
+fn default_queue_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Requests sent at the same time.
+    pub max_in_flight: Option<usize>,
+    pub endpoint_rate: Option<Rate>,
+    /// Applied to every target AGS on its own.
+    pub ags_rate: Option<Rate>,
+    /// Longest time a request waits for being sent.
+    #[serde(rename = "queue_timeout_secs", deserialize_with = "secs")]
+    pub queue_timeout: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_in_flight: None,
+            endpoint_rate: None,
+            ags_rate: None,
+            queue_timeout: default_queue_timeout(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: Rate,
+    /// Negative if requests are already waiting for tokens.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(rate: Rate, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: f64::from(rate.requests),
+            updated: now,
+        }
+    }
+
+    /// Takes a token and returns the time until it is available, or `None`
+    /// if that is longer than `max_wait`.
+    fn reserve(&mut self, now: Instant, max_wait: Duration) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.rate.tokens_per_sec()).min(f64::from(self.rate.requests));
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Some(Duration::ZERO);
+        }
+        let wait = Duration::from_secs_f64((1.0 - self.tokens) / self.rate.tokens_per_sec());
+        if wait > max_wait {
+            return None;
+        }
+        self.tokens -= 1.0;
+        Some(wait)
+    }
+
+    /// Whether the bucket has refilled completely, i.e. is no different
+    /// from a new one.
+    fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * self.rate.tokens_per_sec() >= f64::from(self.rate.requests)
+    }
+
+    fn cancel(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(f64::from(self.rate.requests));
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    queue_timeout: Duration,
+    in_flight: Option<Arc<Semaphore>>,
+    endpoint: Option<Mutex<Bucket>>,
+    ags_rate: Option<Rate>,
+    /// Buckets of the AGS that are not full.
+    ags: Mutex<HashMap<String, Bucket>>,
+}
+
+/// Tokens taken for a request, returned unless [`Reservation::keep`] is
+/// called, e.g. because the caller gave up while waiting.
+struct Reservation<'a> {
+    limiter: &'a Limiter,
+    ziel_ags: Option<&'a str>,
+    kept: bool,
+}
+
+impl Reservation<'_> {
+    fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.kept {
+            self.limiter.cancel(self.ziel_ags);
+        }
+    }
+}
+
+impl Limiter {
+    pub fn new(limits: &Limits) -> Result<Self, LimitError> {
+        if limits.max_in_flight == Some(0) {
+            return Err(LimitError::Invalid("max_in_flight must be positive"));
+        }
+        for rate in [limits.endpoint_rate, limits.ags_rate].iter().flatten() {
+            rate.validate()?;
+        }
+        let now = Instant::now();
+        Ok(Self {
+            queue_timeout: limits.queue_timeout,
+            in_flight: limits.max_in_flight.map(|n| Arc::new(Semaphore::new(n))),
+            endpoint: limits
+                .endpoint_rate
+                .map(|rate| Mutex::new(Bucket::new(rate, now))),
+            ags_rate: limits.ags_rate,
+            ags: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Waits until a request to `ziel_ags` may be sent. The returned permit
+    /// has to be held until the answer is received.
+    pub async fn acquire(
+        &self,
+        ziel_ags: Option<&str>,
+    ) -> Result<Option<OwnedSemaphorePermit>, LimitError> {
+        let start = Instant::now();
+        let ziel_ags = ziel_ags.filter(|_| self.ags_rate.is_some());
+
+        let mut wait = Duration::ZERO;
+        if let Some(endpoint) = self.endpoint.as_ref() {
+            let mut bucket = endpoint.lock().unwrap_or_else(|e| e.into_inner());
+            wait = bucket
+                .reserve(start, self.queue_timeout)
+                .ok_or(LimitError::QueueTimeout(self.queue_timeout))?;
+        }
+        if let (Some(rate), Some(ziel_ags)) = (self.ags_rate, ziel_ags) {
+            let mut buckets = self.ags.lock().unwrap_or_else(|e| e.into_inner());
+            if !buckets.contains_key(ziel_ags) {
+                // full buckets are dropped, so unused AGS do not pile up
+                buckets.retain(|_, bucket| !bucket.is_full(start));
+            }
+            let bucket = buckets
+                .entry(ziel_ags.to_owned())
+                .or_insert_with(|| Bucket::new(rate, start));
+            match bucket.reserve(start, self.queue_timeout) {
+                Some(ags_wait) => wait = wait.max(ags_wait),
+                None => {
+                    drop(buckets);
+                    self.cancel(None);
+                    return Err(LimitError::QueueTimeout(self.queue_timeout));
+                }
+            }
+        }
+        let reservation = Reservation {
+            limiter: self,
+            ziel_ags,
+            kept: false,
+        };
+        tokio::time::sleep(wait).await;
+
+        let Some(semaphore) = self.in_flight.clone() else {
+            reservation.keep();
+            return Ok(None);
+        };
+        match tokio::time::timeout_at(start + self.queue_timeout, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => {
+                reservation.keep();
+                Ok(Some(permit))
+            }
+            _ => Err(LimitError::QueueTimeout(self.queue_timeout)),
+        }
+    }
+
+    /// Returns the tokens of a request that is not sent after all.
+    fn cancel(&self, ziel_ags: Option<&str>) {
+        if let Some(endpoint) = self.endpoint.as_ref() {
+            endpoint.lock().unwrap_or_else(|e| e.into_inner()).cancel();
+        }
+        if let Some(ziel_ags) = ziel_ags {
+            let mut buckets = self.ags.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(bucket) = buckets.get_mut(ziel_ags) {
+                bucket.cancel();
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Limits the requests of this client, see [`Limits`]. Clones of the
+    /// client share the limits.
+    pub fn with_limits(mut self, limits: &Limits) -> Result<Self, LimitError> {
+        self.limiter = Some(Arc::new(Limiter::new(limits)?));
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{LimitError, Limiter, Limits, Rate};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_limiter() {
+        let limiter = Limiter::new(&Limits {
+            max_in_flight: Some(1),
+            ags_rate: Some(Rate::new(1, Duration::from_secs(10))),
+            queue_timeout: Duration::from_secs(5),
+            ..Limits::default()
+        })
+        .unwrap();
+        let permit = limiter.acquire(Some("09162000")).await.unwrap();
+        assert_eq!(
+            limiter.acquire(Some("09162000")).await.unwrap_err(),
+            LimitError::QueueTimeout(Duration::from_secs(5))
+        );
+        // other AGS, but no free slot until the first request is answered
+        let start = tokio::time::Instant::now();
+        assert!(limiter.acquire(Some("05315000")).await.is_err());
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+        drop(permit);
+        assert!(limiter.acquire(Some("05315000")).await.is_ok());
+
+        // the bucket of 09162000 is full again ten seconds after the first request
+        let start = tokio::time::Instant::now();
+        assert!(limiter.acquire(Some("09162000")).await.is_ok());
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancelled_request_returns_tokens() {
+        let limiter = Limiter::new(&Limits {
+            ags_rate: Some(Rate::new(1, Duration::from_secs(10))),
+            ..Limits::default()
+        })
+        .unwrap();
+        assert!(limiter.acquire(Some("09162000")).await.is_ok());
+        // the caller gives up while waiting for the next token
+        let waiting = limiter.acquire(Some("09162000"));
+        assert!(tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .is_err());
+
+        let start = tokio::time::Instant::now();
+        assert!(limiter.acquire(Some("09162000")).await.is_ok());
+        assert_eq!(start.elapsed(), Duration::from_secs(9));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_full_buckets_are_dropped() {
+        let limiter = Limiter::new(&Limits {
+            ags_rate: Some(Rate::new(1, Duration::from_secs(10))),
+            ..Limits::default()
+        })
+        .unwrap();
+        for ags in ["09162000", "05315000", "0916200"] {
+            assert!(limiter.acquire(Some(ags)).await.is_ok());
+        }
+        assert_eq!(limiter.ags.lock().unwrap().len(), 3);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert!(limiter.acquire(Some("02000000")).await.is_ok());
+        assert_eq!(limiter.ags.lock().unwrap().len(), 1);
+        // a dropped bucket starts full again
+        let start = tokio::time::Instant::now();
+        assert!(limiter.acquire(Some("09162000")).await.is_ok());
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_invalid_limits() {
+        for limits in [
+            Limits {
+                ags_rate: Some(Rate::new(0, Duration::from_secs(1))),
+                ..Limits::default()
+            },
+            Limits {
+                endpoint_rate: Some(Rate::new(1, Duration::ZERO)),
+                ..Limits::default()
+            },
+            Limits {
+                max_in_flight: Some(0),
+                ..Limits::default()
+            },
+        ] {
+            assert!(matches!(Limiter::new(&limits), Err(LimitError::Invalid(_))));
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_deserialize_rate() {
+        let limits: Limits =
+            toml::from_str("endpoint_rate = { requests = 5, per_secs = 0.5 }").unwrap();
+        assert_eq!(
+            limits.endpoint_rate,
+            Some(Rate::new(5, Duration::from_millis(500)))
+        );
+        assert!(toml::from_str::<Limits>("ags_rate = { requests = 0, per_secs = 1 }").is_err());
+        assert!(toml::from_str::<Limits>("ags_rate = { requests = 1, per_secs = 0 }").is_err());
+    }
+}
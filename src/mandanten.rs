@@ -0,0 +1,246 @@
+//! MANDANTENANFRAGE: which Mandanten (AGS) an OK.KOMM instance serves.
+//!
+//! There is no published specification of the AKTION. [`Client::mandanten`]
+//! sends Typ [`TYP`] and Ausführung [`AUSFUEHRUNG`] without a target AGS;
+//! instances expecting the SUCHE under a Typ and Mandant of the Verfahren,
+//! e.g. `WEBWAHLSCHEIN`/`ABRUFEN` for `09000011`, are asked with
+//! [`Client::mandanten_for`].
+//!
+//! The answer lists every Mandant with the Verfahren and Typen it supports:
+//!
+//! ```xml
+//! <MANDANTEN>
+//!   <MANDANT>
+//!     <AGS>09162000</AGS>
+//!     <BEZEICHNUNG>Landeshauptstadt München</BEZEICHNUNG>
+//!     <VERFAHREN>
+//!       <AKT_VERFAHREN>EWO</AKT_VERFAHREN>
+//!       <AKT_TYP>WEBWAHLSCHEIN</AKT_TYP>
+//!       <AKT_TYP>AUSKUNFT</AKT_TYP>
+//!     </VERFAHREN>
+//!   </MANDANT>
+//! </MANDANTEN>
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::xml::{WriteXml, XmlWriter};
+use crate::zkoxml::AppsInfo;
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "MANDANTENANFRAGE";
+pub const AUSFUEHRUNG: &str = "ABRUFEN";
+
+/// Default AKTION of a MANDANTENANFRAGE for `verfahren`.
+pub fn aktion(verfahren: &str) -> OkKommAktion {
+    OkKommAktion::new(
+        verfahren.to_owned(),
+        TYP.to_owned(),
+        AUSFUEHRUNG.to_owned(),
+        String::new(),
+    )
+}
+
+/// SUCHE of the request, an empty `<MANDANTENANFRAGE>` element.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Mandantenanfrage;
+
+impl WriteXml for Mandantenanfrage {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), quick_xml::Error> {
+        w.create_element(TYP).write_inner_content(|_| Ok(()))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Mandant {
+    #[serde(rename = "AGS")]
+    pub ags: String,
+    #[serde(rename = "BEZEICHNUNG", default)]
+    pub bezeichnung: String,
+    #[serde(rename = "VERFAHREN", default)]
+    pub verfahren: Vec<MandantVerfahren>,
+}
+
+impl Mandant {
+    /// Whether the Mandant accepts requests for `verfahren` and `typ`.
+    pub fn unterstuetzt(&self, verfahren: &str, typ: &str) -> bool {
+        self.verfahren
+            .iter()
+            .any(|v| v.verfahren == verfahren && v.typen.iter().any(|t| t == typ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct MandantVerfahren {
+    #[serde(rename = "AKT_VERFAHREN")]
+    pub verfahren: String,
+    #[serde(rename = "AKT_TYP", default)]
+    pub typen: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Mandanten {
+    #[serde(rename = "MANDANT", default)]
+    mandanten: Vec<Mandant>,
+}
+
+/// Answers of [`Client::mandanten`] per AKTION, valid for a fixed TTL.
+#[derive(Debug)]
+pub(crate) struct MandantenCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<OkKommAktion, (Instant, Vec<Mandant>)>>,
+}
+
+impl MandantenCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, aktion: &OkKommAktion) -> Option<Vec<Mandant>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(aktion)
+            .filter(|(abgerufen, _)| abgerufen.elapsed() < self.ttl)
+            .map(|(_, mandanten)| mandanten.clone())
+    }
+
+    fn insert(&self, aktion: OkKommAktion, mandanten: &[Mandant]) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(aktion, (Instant::now(), mandanten.to_vec()));
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clear();
+    }
+}
+
+impl Client {
+    /// Caches the answers of [`Client::mandanten`] for `ttl`.
+    pub fn with_mandanten_cache(mut self, ttl: Duration) -> Self {
+        self.mandanten_cache = Some(Arc::new(MandantenCache::new(ttl)));
+        self
+    }
+
+    /// Drops all cached answers of [`Client::mandanten`].
+    pub fn clear_mandanten_cache(&self) {
+        if let Some(cache) = self.mandanten_cache.as_ref() {
+            cache.clear();
+        }
+    }
+
+    /// Sends a MANDANTENANFRAGE for `verfahren` and returns the Mandanten
+    /// served by this OK.KOMM instance.
+    pub async fn mandanten(&self, verfahren: &str) -> anyhow::Result<Vec<Mandant>> {
+        self.mandanten_with_apps_info(verfahren, None).await
+    }
+
+    pub async fn mandanten_with_apps_info(
+        &self,
+        verfahren: &str,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<Vec<Mandant>> {
+        self.mandanten_for(aktion(verfahren), apps_info).await
+    }
+
+    /// Sends a MANDANTENANFRAGE with a custom AKTION, see the
+    /// [module documentation](self).
+    pub async fn mandanten_for(
+        &self,
+        aktion: OkKommAktion,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<Vec<Mandant>> {
+        if let Some(mandanten) = self
+            .mandanten_cache
+            .as_ref()
+            .and_then(|cache| cache.get(&aktion))
+        {
+            return Ok(mandanten);
+        }
+        let response = self
+            .call_uncached(aktion.clone(), Mandantenanfrage, (), apps_info)
+            .await?;
+        let mandanten = response.into_result::<Mandanten>()?.mandanten;
+        if let Some(cache) = self.mandanten_cache.as_ref() {
+            cache.insert(aktion, &mandanten);
+        }
+        Ok(mandanten)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::{testing, OkKommAktion};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_mandanten_cached() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<MANDANTEN><MANDANT><AGS>09162000</AGS><BEZEICHNUNG>München</BEZEICHNUNG><VERFAHREN><AKT_VERFAHREN>EWO</AKT_VERFAHREN><AKT_TYP>WEBWAHLSCHEIN</AKT_TYP><AKT_TYP>AUSKUNFT</AKT_TYP></VERFAHREN></MANDANT><MANDANT><AGS>09000011</AGS></MANDANT></MANDANTEN>",
+            ),
+            move |request| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                assert!(request.contains("<AKT_VERFAHREN>EWO</AKT_VERFAHREN><AKT_TYP>MANDANTENANFRAGE</AKT_TYP>"));
+                assert!(request.contains("<SUCHE><MANDANTENANFRAGE></MANDANTENANFRAGE></SUCHE>"));
+            },
+        )
+        .with_mandanten_cache(Duration::from_secs(60));
+
+        let mandanten = client.mandanten("EWO").await?;
+        assert_eq!(mandanten.len(), 2);
+        assert_eq!(mandanten[0].ags, "09162000");
+        assert!(mandanten[0].unterstuetzt("EWO", "AUSKUNFT"));
+        assert!(!mandanten[1].unterstuetzt("EWO", "AUSKUNFT"));
+
+        assert_eq!(client.mandanten("EWO").await?, mandanten);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        client.clear_mandanten_cache();
+        client.mandanten("EWO").await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        tokio::time::advance(Duration::from_secs(59)).await;
+        client.mandanten("EWO").await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        tokio::time::advance(Duration::from_secs(1)).await;
+        client.mandanten("EWO").await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mandanten_for() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<MANDANTEN><MANDANT><AGS>09000011</AGS></MANDANT></MANDANTEN>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_TYP>WEBWAHLSCHEIN</AKT_TYP><AKT_AUSFUEHRUNG>ABRUFEN</AKT_AUSFUEHRUNG><AKT_ZIEL_AGS>09000011</AKT_ZIEL_AGS>"));
+            },
+        );
+        let aktion = OkKommAktion::new(
+            "EWO".to_owned(),
+            "WEBWAHLSCHEIN".to_owned(),
+            "ABRUFEN".to_owned(),
+            "09000011".to_owned(),
+        );
+        let mandanten = client.mandanten_for(aktion, None).await?;
+        assert_eq!(mandanten[0].ags, "09000011");
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{xml::WriteXml, zkoxml};
+use crate::{redact, xml::WriteXml, zkoxml};
 use base64::{engine::general_purpose::STANDARD, Engine};
 use quick_xml::events::{BytesText, Event};
 use std::io::Cursor;
@@ -19,7 +19,7 @@ pub enum Error {
     FromUtf8Error(#[from] std::string::FromUtf8Error),
 }
 
-#[derive(Debug, Clone, serde::Deserialize, PartialEq)]
+#[derive(Clone, serde::Deserialize, PartialEq)]
 pub struct Base64Body {
     #[serde(rename = "xmlParameter")]
     inner: Option<String>,
@@ -27,6 +27,18 @@ pub struct Base64Body {
     byte_return: Option<String>,
 }
 
+impl std::fmt::Debug for Base64Body {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Base64Body")
+            .field("inner", &redact::opt("xmlParameter", &self.inner))
+            .field(
+                "byte_return",
+                &redact::opt("callApplicationByteReturn", &self.byte_return),
+            )
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, serde::Deserialize, PartialEq)]
 pub struct OkKommCallApplicationByteResponse {
     #[serde(rename = "callApplicationByteResponse")]
@@ -35,20 +47,54 @@ pub struct OkKommCallApplicationByteResponse {
 
 impl OkKommCallApplicationByteResponse {
     pub fn decode(&self) -> Result<(Option<ZkocxmlInfo>, Option<String>), Error> {
-        if let Some(v) = self
-            .bytes
-            .as_ref()
-            .and_then(|v| v.inner.as_deref().or(v.byte_return.as_deref()))
-        {
-            let xml = String::from_utf8(STANDARD.decode(v)?)?;
+        if let Some(xml) = self.zkocxml()? {
             let info = quick_xml::de::from_str::<ZkocxmlInfo>(&xml)?;
             return Ok((Some(info), read_message(&xml)?));
         }
         Ok((None, None))
     }
+
+    /// The base64 decoded ZKOCXML document.
+    pub fn zkocxml(&self) -> Result<Option<String>, Error> {
+        decode_base64_body(self.bytes.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, PartialEq)]
+pub struct OkKommCallApplicationByteRequest {
+    #[serde(rename = "callApplicationByte")]
+    bytes: Option<Base64Body>,
+}
+
+impl OkKommCallApplicationByteRequest {
+    /// The base64 decoded ZKOCXML document.
+    pub fn zkocxml(&self) -> Result<Option<String>, Error> {
+        decode_base64_body(self.bytes.as_ref())
+    }
+}
+
+fn decode_base64_body(body: Option<&Base64Body>) -> Result<Option<String>, Error> {
+    match body.and_then(|v| v.inner.as_deref().or(v.byte_return.as_deref())) {
+        Some(v) => Ok(Some(String::from_utf8(STANDARD.decode(v.trim())?)?)),
+        None => Ok(None),
+    }
+}
+
+/// Wraps a ZKOCXML document into a `callApplicationByteResponse` envelope,
+/// the way OK.KOMM answers.
+pub fn response_envelope(zkocxml: &[u8]) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/"><soapenv:Body><callApplicationByteResponse><callApplicationByteReturn>{}</callApplicationByteReturn></callApplicationByteResponse></soapenv:Body></soapenv:Envelope>"#,
+        STANDARD.encode(zkocxml)
+    )
 }
 
 fn read_message(xml: &str) -> Result<Option<String>, quick_xml::Error> {
+    read_element(xml, b"DATEN")
+}
+
+/// The inner XML of the element called `name`.
+pub(crate) fn read_element(xml: &str, name: &[u8]) -> Result<Option<String>, quick_xml::Error> {
     let mut res = None;
     let mut buf = Vec::new();
     let mut reader = quick_xml::Reader::from_reader(Cursor::new(xml));
@@ -59,13 +105,13 @@ fn read_message(xml: &str) -> Result<Option<String>, quick_xml::Error> {
             Ok(Event::Eof) => break, // exits the loop when reaching end of file
             // Ok(event) => writer.write_event(event),
             Ok(Event::Start(e)) => match e.name().as_ref() {
-                b"DATEN" => {
+                n if n == name => {
                     let mut writer = quick_xml::writer::Writer::new(Vec::new());
                     loop {
                         let ev = reader.read_event_into(&mut buf);
                         match ev {
                             Ok(Event::End(e)) => match e.name().as_ref() {
-                                b"DATEN" => break,
+                                n if n == name => break,
                                 _ => {
                                     writer.write_event(Event::End(e))?;
                                 }
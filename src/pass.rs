@@ -0,0 +1,5 @@
+//! Typed requests for the PASS Verfahren (Pass- und Ausweiswesen).
+
+pub mod dokumentenstatus;
+
+pub const VERFAHREN: &str = "PASS";
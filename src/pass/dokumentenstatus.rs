@@ -0,0 +1,256 @@
+//! PASS DOKUMENTENSTATUS: is a Personalausweis or Reisepass ready for pickup?
+//!
+//! Queried with `OkKommAktion::new("PASS", "DOKUMENTENSTATUS", "ABRUFEN",
+//! <ziel_ags>)`, where `ziel_ags` is the Pass- und Ausweisbehörde the
+//! document was applied for at.
+
+use chrono::NaiveDate;
+
+use crate::pass::VERFAHREN;
+use crate::redact;
+use crate::validation::{self, Validate, ValidationError};
+use crate::xml::{datum, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, AppsInfo, Error};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "DOKUMENTENSTATUS";
+pub const ABRUFEN: &str = "ABRUFEN";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dokumentart {
+    Personalausweis,
+    Reisepass,
+    VorlaeufigerPersonalausweis,
+}
+
+impl Dokumentart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dokumentart::Personalausweis => "PA",
+            Dokumentart::Reisepass => "RP",
+            Dokumentart::VorlaeufigerPersonalausweis => "VPA",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum Dokumentkennung {
+    /// Serial number printed on the document, nine characters.
+    Seriennummer(String),
+    /// Number from the application receipt.
+    Antragsnummer(String),
+}
+
+impl std::fmt::Debug for Dokumentkennung {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dokumentkennung::Seriennummer(v) => f
+                .debug_tuple("Seriennummer")
+                .field(&redact::value("SERIENNUMMER", v))
+                .finish(),
+            Dokumentkennung::Antragsnummer(v) => f
+                .debug_tuple("Antragsnummer")
+                .field(&redact::value("ANTRAGSNUMMER", v))
+                .finish(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DokumentenstatusAnfrage {
+    pub dokumentart: Dokumentart,
+    pub kennung: Dokumentkennung,
+}
+
+impl DokumentenstatusAnfrage {
+    pub fn seriennummer<S: ToString>(dokumentart: Dokumentart, seriennummer: S) -> Self {
+        Self {
+            dokumentart,
+            kennung: Dokumentkennung::Seriennummer(seriennummer.to_string()),
+        }
+    }
+
+    pub fn antragsnummer<S: ToString>(dokumentart: Dokumentart, antragsnummer: S) -> Self {
+        Self {
+            dokumentart,
+            kennung: Dokumentkennung::Antragsnummer(antragsnummer.to_string()),
+        }
+    }
+}
+
+impl Validate for DokumentenstatusAnfrage {
+    fn validate(&self) -> Result<(), ValidationError> {
+        match &self.kennung {
+            Dokumentkennung::Seriennummer(v) => {
+                validation::required("SERIENNUMMER", v)?;
+                if v.len() != 9 || !v.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                    return Err(validation::invalid(
+                        "SERIENNUMMER",
+                        "expected nine letters or digits",
+                    ));
+                }
+                Ok(())
+            }
+            Dokumentkennung::Antragsnummer(v) => validation::required("ANTRAGSNUMMER", v),
+        }
+    }
+}
+
+impl WriteXml for DokumentenstatusAnfrage {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element(TYP).write_inner_content(|w| {
+            write_field_opt(w, "DOKUMENTART", Some(self.dokumentart.as_str()))?;
+            match &self.kennung {
+                Dokumentkennung::Seriennummer(v) => {
+                    write_field_opt(w, "SERIENNUMMER", Some(v.to_uppercase().as_str()))?
+                }
+                Dokumentkennung::Antragsnummer(v) => {
+                    write_field_opt(w, "ANTRAGSNUMMER", Some(v.as_str()))?
+                }
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(from = "String")]
+pub enum Dokumentenstatus {
+    InProduktion,
+    Abholbereit,
+    Ausgehaendigt,
+    Unbekannt(String),
+}
+
+impl From<String> for Dokumentenstatus {
+    fn from(value: String) -> Self {
+        match value.trim() {
+            "IN_PRODUKTION" => Self::InProduktion,
+            "ABHOLBEREIT" => Self::Abholbereit,
+            "AUSGEHAENDIGT" => Self::Ausgehaendigt,
+            _ => Self::Unbekannt(value),
+        }
+    }
+}
+
+/// Address of an [`Ausgabestelle`]. Unlike [`crate::ewo::Anschrift`] every
+/// part is optional, offices are often given by street or PLZ only.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default)]
+pub struct AusgabestellenAnschrift {
+    #[serde(rename = "STRASSE")]
+    pub strasse: Option<String>,
+    #[serde(rename = "HAUSNUMMER")]
+    pub hausnummer: Option<String>,
+    #[serde(rename = "ZUSATZ")]
+    pub zusatz: Option<String>,
+    #[serde(rename = "PLZ")]
+    pub plz: Option<String>,
+    #[serde(rename = "ORT")]
+    pub ort: Option<String>,
+}
+
+/// The office handing out the document.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Ausgabestelle {
+    #[serde(rename = "BEZEICHNUNG")]
+    pub bezeichnung: String,
+    #[serde(rename = "ANSCHRIFT", default)]
+    pub anschrift: Option<AusgabestellenAnschrift>,
+    #[serde(rename = "TELEFON", default)]
+    pub telefon: Option<String>,
+    #[serde(rename = "EMAIL", default)]
+    pub email: Option<String>,
+    #[serde(rename = "OEFFNUNGSZEITEN", default)]
+    pub oeffnungszeiten: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct DokumentenstatusAuskunft {
+    #[serde(rename = "STATUS")]
+    pub status: Dokumentenstatus,
+    #[serde(
+        rename = "ABHOLBEREIT_SEIT",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub abholbereit_seit: Option<NaiveDate>,
+    #[serde(rename = "AUSGABESTELLE", default)]
+    pub ausgabestelle: Option<Ausgabestelle>,
+}
+
+impl Client {
+    pub async fn dokumentenstatus(
+        &self,
+        ziel_ags: &str,
+        anfrage: &DokumentenstatusAnfrage,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<DokumentenstatusAuskunft> {
+        anfrage.validate()?;
+        let aktion = OkKommAktion::new(
+            VERFAHREN.to_owned(),
+            TYP.to_owned(),
+            ABRUFEN.to_owned(),
+            ziel_ags.to_owned(),
+        );
+        self.call(aktion, anfrage, (), apps_info)
+            .await?
+            .into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{Dokumentart, Dokumentenstatus, DokumentenstatusAnfrage, DokumentenstatusAuskunft};
+    use crate::testing;
+
+    #[tokio::test]
+    async fn test_dokumentenstatus() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<DOKUMENTENSTATUS><STATUS>ABHOLBEREIT</STATUS><ABHOLBEREIT_SEIT>14.02.2025</ABHOLBEREIT_SEIT><AUSGABESTELLE><BEZEICHNUNG>Bürgerbüro Mitte</BEZEICHNUNG><ANSCHRIFT><STRASSE>Marienplatz</STRASSE><HAUSNUMMER>8</HAUSNUMMER><PLZ>80331</PLZ><ORT>München</ORT></ANSCHRIFT><OEFFNUNGSZEITEN>Mo-Fr 8-12 Uhr</OEFFNUNGSZEITEN></AUSGABESTELLE></DOKUMENTENSTATUS>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_VERFAHREN>PASS</AKT_VERFAHREN><AKT_TYP>DOKUMENTENSTATUS</AKT_TYP><AKT_AUSFUEHRUNG>ABRUFEN</AKT_AUSFUEHRUNG><AKT_ZIEL_AGS>09162000</AKT_ZIEL_AGS>"));
+                assert!(request.contains("<SUCHE><DOKUMENTENSTATUS><DOKUMENTART>PA</DOKUMENTART><SERIENNUMMER>L01X00T47</SERIENNUMMER></DOKUMENTENSTATUS></SUCHE>"));
+            },
+        );
+        let auskunft = client
+            .dokumentenstatus(
+                "09162000",
+                &DokumentenstatusAnfrage::seriennummer(Dokumentart::Personalausweis, "l01x00t47"),
+                None,
+            )
+            .await?;
+        assert_eq!(auskunft.status, Dokumentenstatus::Abholbereit);
+        assert_eq!(
+            auskunft.abholbereit_seit,
+            NaiveDate::from_ymd_opt(2025, 2, 14)
+        );
+        assert_eq!(
+            auskunft.ausgabestelle.unwrap().bezeichnung,
+            "Bürgerbüro Mitte"
+        );
+
+        let auskunft: DokumentenstatusAuskunft = quick_xml::de::from_str(
+            "<DOKUMENTENSTATUS><STATUS>ABHOLBEREIT</STATUS><AUSGABESTELLE><BEZEICHNUNG>Bürgerbüro Pasing</BEZEICHNUNG><ANSCHRIFT><STRASSE>Landsberger Straße</STRASSE><ORT>München</ORT></ANSCHRIFT></AUSGABESTELLE></DOKUMENTENSTATUS>",
+        )?;
+        let anschrift = auskunft.ausgabestelle.unwrap().anschrift.unwrap();
+        assert_eq!(anschrift.hausnummer, None);
+        assert_eq!(anschrift.ort.as_deref(), Some("München"));
+
+        assert!(client
+            .dokumentenstatus(
+                "09162000",
+                &DokumentenstatusAnfrage::seriennummer(Dokumentart::Reisepass, "123"),
+                None,
+            )
+            .await
+            .is_err());
+        Ok(())
+    }
+}
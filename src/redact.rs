@@ -0,0 +1,234 @@
+//! Redaction of personal data in error messages, log lines and `Debug` output.
+//!
+//! Rules are element names (without namespace prefix). The text content of
+//! every matching element is replaced by [`REDACTED`]; fields of the crate's
+//! types that correspond to such an element print as [`REDACTED`] in their
+//! `Debug` output. The rules are process wide, see [`set_redactor`].
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+pub const REDACTED: &str = "***";
+
+pub const DEFAULT_ELEMENTS: &[&str] = &[
+    "NAME",
+    "VORNAME",
+    "FAMILIENNAME",
+    "GEBURTSNAME",
+    "GEBURTSDATUM",
+    "GEBURTSORT",
+    "STRASSE",
+    "HAUSNUMMER",
+    "ZUSATZ",
+    "PLZ",
+    "ORT",
+    "STAATSANGEHOERIGKEIT",
+    "TELEFON",
+    "EMAIL",
+    "KENNZEICHEN",
+    "WAEHLERVERZEICHNIS_NR",
+    "SERIENNUMMER",
+    "ANTRAGSNUMMER",
+    "AKT_TECHPWD",
+    "APPS_KENNUNG",
+    "APPS_IP_ADRESSE",
+    "FEH_TEXT",
+    "FEH_WERT",
+    "OK_KOMM_RAW_BASE64",
+    "xmlParameter",
+    "callApplicationByteReturn",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redactor {
+    elements: BTreeSet<String>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_ELEMENTS.iter().copied())
+    }
+}
+
+impl Redactor {
+    pub fn new<I, S>(elements: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            elements: elements.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// A redactor without any rules, i.e. everything is printed verbatim.
+    pub fn none() -> Self {
+        Self {
+            elements: BTreeSet::new(),
+        }
+    }
+
+    pub fn with_element<S: Into<String>>(mut self, element: S) -> Self {
+        self.elements.insert(element.into());
+        self
+    }
+
+    pub fn without_element(mut self, element: &str) -> Self {
+        self.elements.remove(element);
+        self
+    }
+
+    pub fn is_redacted(&self, element: &str) -> bool {
+        let local = element.rsplit(':').next().unwrap_or(element);
+        self.elements.contains(local)
+    }
+
+    /// Replaces the text content of every element matching a rule.
+    ///
+    /// Works on well-formed XML as well as on XML snippets embedded in
+    /// arbitrary text, e.g. a `{:?}` formatted response body.
+    pub fn redact_xml<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.elements.is_empty() {
+            return Cow::Borrowed(text);
+        }
+        let mut out = String::new();
+        let mut copied = 0;
+        let mut pos = 0;
+        while let Some(offset) = text[pos..].find('<') {
+            let start = pos + offset;
+            let Some(tag_len) = text[start..].find('>') else {
+                break;
+            };
+            let tag_end = start + tag_len;
+            pos = tag_end + 1;
+            let tag = &text[start + 1..tag_end];
+            if tag.starts_with(['/', '?', '!']) || tag.ends_with('/') {
+                continue;
+            }
+            let name = tag
+                .split(|c: char| c.is_whitespace() || c == '\\')
+                .next()
+                .unwrap_or_default();
+            if name.is_empty() || !self.is_redacted(name) {
+                continue;
+            }
+            let close = format!("</{name}>");
+            if let Some(close_offset) = text[pos..].find(&close) {
+                out.push_str(&text[copied..pos]);
+                out.push_str(REDACTED);
+                copied = pos + close_offset;
+                pos = copied + close.len();
+            }
+        }
+        if copied == 0 {
+            return Cow::Borrowed(text);
+        }
+        out.push_str(&text[copied..]);
+        Cow::Owned(out)
+    }
+}
+
+fn global() -> &'static RwLock<Redactor> {
+    static REDACTOR: OnceLock<RwLock<Redactor>> = OnceLock::new();
+    REDACTOR.get_or_init(|| RwLock::new(Redactor::default()))
+}
+
+/// Replaces the process wide redaction rules.
+pub fn set_redactor(redactor: Redactor) {
+    match global().write() {
+        Ok(mut guard) => *guard = redactor,
+        Err(poisoned) => *poisoned.into_inner() = redactor,
+    }
+}
+
+pub fn redactor() -> Redactor {
+    match global().read() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Applies the process wide rules to `text`.
+pub fn text(text: &str) -> String {
+    redactor().redact_xml(text).into_owned()
+}
+
+pub(crate) fn is_redacted(element: &str) -> bool {
+    match global().read() {
+        Ok(guard) => guard.is_redacted(element),
+        Err(poisoned) => poisoned.into_inner().is_redacted(element),
+    }
+}
+
+/// `Debug` and `Display` helper for a field backed by the XML element
+/// `element`.
+pub(crate) struct Value<'a> {
+    element: &'static str,
+    value: &'a str,
+}
+
+pub(crate) fn value<'a>(element: &'static str, value: &'a str) -> Value<'a> {
+    Value { element, value }
+}
+
+pub(crate) fn opt<'a>(element: &'static str, v: &'a Option<String>) -> Option<Value<'a>> {
+    v.as_deref().map(|v| value(element, v))
+}
+
+impl fmt::Debug for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_redacted(self.element) {
+            fmt::Debug::fmt(REDACTED, f)
+        } else {
+            fmt::Debug::fmt(self.value, f)
+        }
+    }
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_redacted(self.element) {
+            f.write_str(REDACTED)
+        } else {
+            f.write_str(self.value)
+        }
+    }
+}
+
+/// `Debug` helper for a field holding an XML document or fragment.
+pub(crate) struct Xml<'a>(pub &'a str);
+
+impl fmt::Debug for Xml<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&text(self.0), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Redactor;
+
+    #[test]
+    fn test_redact_xml() {
+        let redactor = Redactor::default();
+        assert_eq!(
+            redactor.redact_xml(
+                r#"<PERSON><NAME>Mustermann</NAME><VORNAME/><ewo:GEBURTSDATUM a="1">01.01.1970</ewo:GEBURTSDATUM><AGS>09162000</AGS></PERSON>"#
+            ),
+            r#"<PERSON><NAME>***</NAME><VORNAME/><ewo:GEBURTSDATUM a="1">***</ewo:GEBURTSDATUM><AGS>09162000</AGS></PERSON>"#
+        );
+        assert_eq!(
+            Redactor::none().redact_xml("<NAME>Mustermann</NAME>"),
+            "<NAME>Mustermann</NAME>"
+        );
+        assert_eq!(
+            redactor
+                .without_element("NAME")
+                .with_element("AGS")
+                .redact_xml("\"<NAME>Mustermann</NAME><AGS>09162000</AGS>\""),
+            "\"<NAME>Mustermann</NAME><AGS>***</AGS>\""
+        );
+    }
+}
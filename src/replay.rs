@@ -0,0 +1,387 @@
+//! Recording and replaying of OK.KOMM traffic for regression tests.
+//!
+//! [`RecordingTransport`] wraps another [`Transport`] and writes every
+//! exchange, redacted, into a JSON fixture file. [`ReplayTransport`] answers
+//! requests from such a file without any network access. Requests are matched
+//! by AKTION and the SHA-256 of the normalized, unredacted SUCHE, so requests
+//! that differ only in redacted values get their own answers; volatile fields
+//! like APPS_DATUM or APPS_UHRZEIT are ignored.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use anyhow::Error;
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+
+use crate::okkomm::{
+    read_element, response_envelope, OkKommCallApplicationByteRequest,
+    OkKommCallApplicationByteResponse,
+};
+use crate::redact::{self, Redactor};
+use crate::soap::SoapResponse;
+use crate::transport::{BoxFuture, Transport, TransportResponse};
+use crate::xml;
+use crate::zkoxml::ZkocxmlInfo;
+use crate::OkKommAktion;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Exchange {
+    pub aktion: OkKommAktion,
+    /// Normalized and redacted content of SUCHE, for information only.
+    pub suche: String,
+    /// Hex encoded SHA-256 of the normalized, unredacted SUCHE, used for
+    /// matching.
+    pub suche_sha256: String,
+    /// Redacted ZKOCXML of the request, for information only.
+    pub request: String,
+    pub status: u16,
+    /// Redacted ZKOCXML of the response, if the answer could be decoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    /// Raw body of the answer otherwise, e.g. a SOAP fault.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Fixture {
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Fixture {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+struct RecordedRequest {
+    aktion: OkKommAktion,
+    suche: String,
+    suche_sha256: String,
+    zkocxml: String,
+}
+
+fn inspect_request(body: &[u8], redactor: &Redactor) -> anyhow::Result<RecordedRequest> {
+    let envelope =
+        SoapResponse::<OkKommCallApplicationByteRequest>::from_str(&String::from_utf8_lossy(body))?;
+    let zkocxml = envelope
+        .into_inner()
+        .map(|request| request.zkocxml())
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| Error::msg("SOAP request does not contain a ZKOCXML document"))?;
+    let aktion = quick_xml::de::from_str::<ZkocxmlInfo>(&zkocxml)?
+        .xml_system
+        .system
+        .aktion
+        .unwrap_or_default();
+    let suche = xml::normalize(&read_element(&zkocxml, b"SUCHE")?.unwrap_or_default())?;
+    Ok(RecordedRequest {
+        aktion: OkKommAktion::new(
+            aktion.verfahren.unwrap_or_default(),
+            aktion.typ.unwrap_or_default(),
+            aktion.ausfuehrung.unwrap_or_default(),
+            aktion.ziel_ags.unwrap_or_default(),
+        ),
+        suche_sha256: format!("{:x}", Sha256::digest(suche.as_bytes())),
+        suche: redactor.redact_xml(&suche).into_owned(),
+        zkocxml: redactor.redact_xml(&zkocxml).into_owned(),
+    })
+}
+
+/// Passes requests on to `inner` and records every exchange into a fixture file.
+pub struct RecordingTransport<T> {
+    inner: T,
+    path: PathBuf,
+    redactor: Redactor,
+    fixture: Mutex<Fixture>,
+}
+
+impl<T> RecordingTransport<T>
+where
+    T: Transport,
+{
+    /// Records into `path`, appending to the exchanges already stored there.
+    pub fn new(inner: T, path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let fixture = if path.exists() {
+            Fixture::load(&path)?
+        } else {
+            Fixture::default()
+        };
+        Ok(Self {
+            inner,
+            path,
+            redactor: redact::redactor(),
+            fixture: Mutex::new(fixture),
+        })
+    }
+
+    /// Redaction rules for the fixture, defaults to the process wide rules.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    fn record(&self, request: RecordedRequest, response: &TransportResponse) -> anyhow::Result<()> {
+        let response_text = String::from_utf8_lossy(&response.body);
+        let zkocxml = SoapResponse::<OkKommCallApplicationByteResponse>::from_str(&response_text)
+            .ok()
+            .and_then(|envelope| envelope.into_inner())
+            .and_then(|response| response.zkocxml().ok().flatten());
+        let exchange = Exchange {
+            aktion: request.aktion,
+            suche: request.suche,
+            suche_sha256: request.suche_sha256,
+            request: request.zkocxml,
+            status: response.status,
+            response_body: match zkocxml {
+                Some(_) => None,
+                None => Some(self.redactor.redact_xml(&response_text).into_owned()),
+            },
+            response: zkocxml.map(|xml| self.redactor.redact_xml(&xml).into_owned()),
+        };
+        let mut fixture = self
+            .fixture
+            .lock()
+            .map_err(|_| Error::msg("fixture lock poisoned"))?;
+        fixture.exchanges.push(exchange);
+        fixture.save(&self.path)
+    }
+}
+
+impl<T> Transport for RecordingTransport<T>
+where
+    T: Transport,
+{
+    fn send(&self, body: Bytes) -> BoxFuture<'_, TransportResponse> {
+        Box::pin(async move {
+            let request = inspect_request(&body, &self.redactor);
+            let response = self.inner.send(body).await?;
+            match request {
+                Ok(request) => {
+                    if let Err(err) = self.record(request, &response) {
+                        log::error!(
+                            "{}",
+                            redact::text(&format!(
+                                "Error while recording OK.KOMM exchange: {err:#?}"
+                            ))
+                        );
+                    }
+                }
+                Err(err) => log::error!(
+                    "{}",
+                    redact::text(&format!(
+                        "Error while inspecting OK.KOMM request for recording: {err:#?}"
+                    ))
+                ),
+            }
+            Ok(response)
+        })
+    }
+
+    fn url(&self) -> Option<&str> {
+        self.inner.url()
+    }
+}
+
+/// Answers requests from a fixture file written by [`RecordingTransport`].
+///
+/// If several exchanges match a request, they are replayed in recording
+/// order; the last one is repeated once all have been used.
+pub struct ReplayTransport {
+    fixture: Fixture,
+    redactor: Redactor,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayTransport {
+    pub fn new(fixture: Fixture) -> Self {
+        let used = vec![false; fixture.exchanges.len()];
+        Self {
+            fixture,
+            redactor: redact::redactor(),
+            used: Mutex::new(used),
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Ok(Self::new(Fixture::load(path)?))
+    }
+
+    /// Redaction rules for the SUCHE of unmatched requests in error
+    /// messages, defaults to the process wide rules.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    fn replay(&self, body: &[u8]) -> anyhow::Result<TransportResponse> {
+        let request = inspect_request(body, &self.redactor)?;
+        let matches: Vec<usize> = self
+            .fixture
+            .exchanges
+            .iter()
+            .enumerate()
+            .filter(|(_, exchange)| {
+                exchange.aktion == request.aktion && exchange.suche_sha256 == request.suche_sha256
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let mut used = self
+            .used
+            .lock()
+            .map_err(|_| Error::msg("replay lock poisoned"))?;
+        let index = matches
+            .iter()
+            .copied()
+            .find(|index| !used[*index])
+            .or(matches.last().copied())
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "No recorded OK.KOMM exchange matches {:?} with SUCHE {:?}",
+                    request.aktion, request.suche
+                ))
+            })?;
+        used[index] = true;
+        let exchange = &self.fixture.exchanges[index];
+        let body = match (&exchange.response, &exchange.response_body) {
+            (Some(zkocxml), _) => response_envelope(zkocxml.as_bytes()),
+            (None, Some(body)) => body.clone(),
+            (None, None) => String::new(),
+        };
+        Ok(TransportResponse::new(exchange.status, body))
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, body: Bytes) -> BoxFuture<'_, TransportResponse> {
+        let response = self.replay(&body);
+        Box::pin(async move { response })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{Fixture, RecordingTransport, ReplayTransport};
+    use crate::okkomm::response_envelope;
+    use crate::redact::Redactor;
+    use crate::transport::{MemoryTransport, TransportResponse};
+    use crate::zkoxml::RawRequest;
+    use crate::{Client, OkKommAktion};
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Person {
+        #[serde(rename = "NAME")]
+        name: String,
+        #[serde(rename = "ORT")]
+        ort: String,
+    }
+
+    fn aktion() -> OkKommAktion {
+        OkKommAktion::new(
+            "EWO".to_owned(),
+            "AUSKUNFT".to_owned(),
+            "ABRUFEN".to_owned(),
+            "09162000".to_owned(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let path = std::env::temp_dir().join(format!("okkomm-replay-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let redactor = Redactor::new(["NAME"]);
+
+        let recorded = AtomicUsize::new(0);
+        let recording = RecordingTransport::new(
+            MemoryTransport::new(move |_| {
+                let ort = match recorded.fetch_add(1, Ordering::SeqCst) {
+                    0 => "München",
+                    _ => "Köln",
+                };
+                Ok(TransportResponse::new(
+                    200,
+                    response_envelope(
+                        format!("<ZKOCXML><XML_SYSTEM><SYSTEM/></XML_SYSTEM><XML_DATEN><DATEN><PERSON><NAME>Mustermann</NAME><ORT>{ort}</ORT></PERSON></DATEN></XML_DATEN></ZKOCXML>").as_bytes(),
+                    ),
+                ))
+            }),
+            &path,
+        )?
+        .with_redactor(redactor.clone());
+        let client = Client::from_transport(recording);
+        let person: Person = client
+            .send_request_xml(
+                aktion(),
+                RawRequest("<PERSON>\n  <NAME>Mustermann</NAME>\n</PERSON>".to_owned()),
+                None,
+            )
+            .await?;
+        assert_eq!(person.name, "Mustermann");
+        let _: Person = client
+            .send_request_xml(
+                aktion(),
+                RawRequest("<PERSON><NAME>Musterfrau</NAME></PERSON>".to_owned()),
+                None,
+            )
+            .await?;
+
+        let fixture = Fixture::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(fixture.exchanges.len(), 2);
+        assert_eq!(
+            fixture.exchanges[0].suche,
+            "<PERSON><NAME>***</NAME></PERSON>"
+        );
+        assert!(!fixture.exchanges[0].suche_sha256.is_empty());
+
+        // requests that differ only in redacted values get their own answers
+        let replay = Client::from_transport(ReplayTransport::new(fixture).with_redactor(redactor));
+        for (name, ort) in [
+            ("Musterfrau", "Köln"),
+            ("Mustermann", "München"),
+            ("Musterfrau", "Köln"),
+        ] {
+            let person: Person = replay
+                .send_request_xml(
+                    aktion(),
+                    RawRequest(format!("<PERSON><NAME>{name}</NAME></PERSON>")),
+                    None,
+                )
+                .await?;
+            assert_eq!(
+                person,
+                Person {
+                    name: "***".to_owned(),
+                    ort: ort.to_owned()
+                }
+            );
+        }
+        assert!(replay
+            .send_request_xml::<_, Person>(
+                aktion(),
+                RawRequest("<PERSON><NAME>Beispiel</NAME></PERSON>".to_owned()),
+                None
+            )
+            .await
+            .is_err());
+        assert!(replay
+            .send_request_xml::<_, Person>(aktion(), RawRequest("<ANDERE_SUCHE/>".to_owned()), None)
+            .await
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! Decoded OK.KOMM answers and business errors (FEHLER).
+
+use serde::Deserialize;
+
+use crate::parse_daten;
+use crate::redact;
+use crate::zkoxml::{ZkocxmlError, ZkocxmlInfo};
+
+/// ZKOCXML SYSTEM part and DATEN of an OK.KOMM answer.
+#[derive(Clone, PartialEq)]
+pub struct OkKommResponse {
+    pub info: Option<ZkocxmlInfo>,
+    pub daten: Option<String>,
+}
+
+impl std::fmt::Debug for OkKommResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OkKommResponse")
+            .field("info", &self.info)
+            .field("daten", &self.daten.as_deref().map(redact::Xml))
+            .finish()
+    }
+}
+
+impl OkKommResponse {
+    pub fn error(&self) -> Option<ZkocxmlError<'_>> {
+        self.info.as_ref().and_then(|info| info.error())
+    }
+
+    pub fn deserialize<R>(&self) -> anyhow::Result<R>
+    where
+        R: for<'a> Deserialize<'a>,
+    {
+        parse_daten(self.info.as_ref(), self.daten.as_deref())
+    }
+
+    /// Deserializes DATEN, or fails with an [`OkKommFehler`] if the answer
+    /// carries a FEHLER.
+    pub fn into_result<R>(self) -> anyhow::Result<R>
+    where
+        R: for<'a> Deserialize<'a>,
+    {
+        if let Some(err) = self.error() {
+            return Err(OkKommFehler::from(err).into());
+        }
+        self.deserialize()
+    }
+}
+
+/// FEHLER reported by the OK.KOMM backend. The text is redacted like the
+/// FEH_TEXT element, as it may quote register values.
+#[derive(Clone, PartialEq, Eq, thiserror::Error)]
+#[error("OK.KOMM FEHLER {typ}: {}", redact::value("FEH_TEXT", .text))]
+pub struct OkKommFehler {
+    pub typ: String,
+    pub text: String,
+    pub wert: String,
+    pub feld: String,
+}
+
+impl From<ZkocxmlError<'_>> for OkKommFehler {
+    fn from(err: ZkocxmlError<'_>) -> Self {
+        Self {
+            typ: err.typ.to_owned(),
+            text: err.text.to_owned(),
+            wert: err.wert.to_owned(),
+            feld: err.feld.to_owned(),
+        }
+    }
+}
+
+impl std::fmt::Debug for OkKommFehler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OkKommFehler")
+            .field("typ", &redact::value("FEH_TYP", &self.typ))
+            .field("text", &redact::value("FEH_TEXT", &self.text))
+            .field("wert", &redact::value("FEH_WERT", &self.wert))
+            .field("feld", &redact::value("FEH_FELD", &self.feld))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OkKommFehler, OkKommResponse};
+
+    #[test]
+    fn test_debug_redacts_daten() {
+        let response = OkKommResponse {
+            info: None,
+            daten: Some("<PERSON><NAME>Mustermann</NAME><AGS>09162000</AGS></PERSON>".to_owned()),
+        };
+        let debug = format!("{response:?}");
+        assert!(!debug.contains("Mustermann"));
+        assert!(debug.contains("09162000"));
+    }
+
+    #[test]
+    fn test_display_redacts_fehler_text() {
+        let fehler = OkKommFehler {
+            typ: "PERSON_UNBEKANNT".to_owned(),
+            text: "Erika Mustermann ist nicht gemeldet".to_owned(),
+            wert: String::new(),
+            feld: String::new(),
+        };
+        assert_eq!(fehler.to_string(), "OK.KOMM FEHLER PERSON_UNBEKANNT: ***");
+    }
+}
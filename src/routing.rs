@@ -0,0 +1,252 @@
+//! Routing of requests to different OK.KOMM instances by target AGS.
+//!
+//! A [`RoutingClient`] holds one [`Client`] per AGS prefix, e.g. `09` for
+//! a Land, `091` for a Regierungsbezirk or `09162` for a Kreis, and picks the
+//! one with the longest prefix of `OkKommAktion::ziel_ags`. Requests that
+//! match no prefix go to the default client, if any.
+//!
+//! ```toml
+//! [default]
+//! url = "https://okkomm.example.org/KomService"
+//!
+//! [routes.09]
+//! url = "https://okkomm.bayern.example.org/KomService"
+//!
+//! [routes.09162]
+//! url = "https://okkomm.muenchen.example.org/KomService"
+//! credentials = { techuser = "portal", techpwd_file = "/run/secrets/muenchen" }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::{parse_file, ClientConfig, ConfigError};
+use crate::response::OkKommResponse;
+use crate::transport::TransportResponse;
+use crate::xml::WriteXml;
+use crate::zkoxml::{AppsInfo, ContentContainerAttachment};
+use crate::{Client, OkKommAktion};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RoutingError {
+    #[error("no OK.KOMM endpoint configured for AGS {0:?}")]
+    NoRoute(String),
+    #[error("invalid AGS prefix {0:?}, expected one to eight digits")]
+    InvalidPrefix(String),
+    #[error("AGS prefix {0:?} is configured twice")]
+    DuplicatePrefix(String),
+}
+
+/// [`ClientConfig`] per AGS prefix plus an optional default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingConfig {
+    #[serde(default)]
+    pub default: Option<ClientConfig>,
+    #[serde(default)]
+    pub routes: BTreeMap<String, ClientConfig>,
+}
+
+impl RoutingConfig {
+    /// Reads a `.toml`, `.yaml` or `.yml` file, see
+    /// [`ClientConfig::from_file`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        parse_file(path.as_ref())
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct RoutingClient {
+    /// Sorted by descending prefix length, so the first match is the most
+    /// specific one.
+    routes: Vec<(String, Client)>,
+    default: Option<Client>,
+}
+
+impl RoutingClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends requests for every AGS starting with `ags_prefix` to `client`.
+    pub fn with_route(
+        mut self,
+        ags_prefix: impl Into<String>,
+        client: Client,
+    ) -> Result<Self, RoutingError> {
+        let ags_prefix = ags_prefix.into();
+        if !(1..=8).contains(&ags_prefix.len()) || !ags_prefix.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RoutingError::InvalidPrefix(ags_prefix));
+        }
+        if self.routes.iter().any(|(prefix, _)| *prefix == ags_prefix) {
+            return Err(RoutingError::DuplicatePrefix(ags_prefix));
+        }
+        self.routes.push((ags_prefix, client));
+        self.routes
+            .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        Ok(self)
+    }
+
+    /// Sends requests matching no prefix to `client`.
+    pub fn with_default(mut self, client: Client) -> Self {
+        self.default = Some(client);
+        self
+    }
+
+    pub fn from_config(config: &RoutingConfig) -> anyhow::Result<Self> {
+        let mut routing = Self::new();
+        for (ags_prefix, client) in &config.routes {
+            routing = routing.with_route(ags_prefix.clone(), Client::from_config(client)?)?;
+        }
+        if let Some(default) = config.default.as_ref() {
+            routing = routing.with_default(Client::from_config(default)?);
+        }
+        Ok(routing)
+    }
+
+    /// Client for `ziel_ags`, e.g. to use the typed requests of a Verfahren.
+    pub fn client_for(&self, ziel_ags: &str) -> Result<&Client, RoutingError> {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| ziel_ags.starts_with(prefix.as_str()))
+            .map(|(_, client)| client)
+            .or(self.default.as_ref())
+            .ok_or_else(|| RoutingError::NoRoute(ziel_ags.to_owned()))
+    }
+
+    pub async fn send_request<T>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<TransportResponse>
+    where
+        T: WriteXml,
+    {
+        self.client_for(&info.ziel_ags)?
+            .send_request(info, body, apps_info)
+            .await
+    }
+
+    /// See [`Client::call`].
+    pub async fn call<R, D>(
+        &self,
+        info: OkKommAktion,
+        request: R,
+        data: impl Into<Option<D>>,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<OkKommResponse>
+    where
+        R: WriteXml,
+        D: WriteXml,
+    {
+        self.client_for(&info.ziel_ags)?
+            .call(info, request, data, apps_info)
+            .await
+    }
+
+    pub async fn send_request_xml<T, R>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<R>
+    where
+        T: WriteXml,
+        R: for<'a> Deserialize<'a>,
+    {
+        self.client_for(&info.ziel_ags)?
+            .send_request_xml(info, body, apps_info)
+            .await
+    }
+
+    pub async fn send_request_xml_base64<T, R>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<R>
+    where
+        T: WriteXml,
+        R: for<'a> Deserialize<'a>,
+    {
+        self.client_for(&info.ziel_ags)?
+            .send_request_xml_base64(info, body, apps_info)
+            .await
+    }
+
+    pub async fn send_request_xml_in_content_container<T, R>(
+        &self,
+        info: OkKommAktion,
+        body: T,
+        attachments: Vec<ContentContainerAttachment>,
+        ref_id: String,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<R>
+    where
+        T: WriteXml,
+        R: for<'a> Deserialize<'a>,
+    {
+        self.client_for(&info.ziel_ags)?
+            .send_request_xml_in_content_container(info, body, attachments, ref_id, apps_info)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RoutingClient, RoutingError};
+    use crate::testing;
+    use crate::zkoxml::RawRequest;
+    use crate::OkKommAktion;
+
+    fn aktion(ziel_ags: &str) -> OkKommAktion {
+        OkKommAktion::new(
+            "EWO".to_owned(),
+            "MANDANTENANFRAGE".to_owned(),
+            "ABRUFEN".to_owned(),
+            ziel_ags.to_owned(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_routing() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let endpoint = |name: &str| {
+            testing::client(
+                testing::zkocxml("", &format!("<ENDPUNKT>{name}</ENDPUNKT>")),
+                |_| {},
+            )
+        };
+        let routing = RoutingClient::new()
+            .with_route("09", endpoint("bayern"))?
+            .with_route("09162", endpoint("muenchen"))?;
+        for (ziel_ags, expected) in [("09162000", "muenchen"), ("09184119", "bayern")] {
+            let response = routing
+                .call(aktion(ziel_ags), RawRequest(String::new()), (), None)
+                .await?;
+            assert_eq!(
+                response.daten.as_deref(),
+                Some(format!("<ENDPUNKT>{expected}</ENDPUNKT>").as_str())
+            );
+        }
+        assert_eq!(
+            routing.client_for("05315000").err(),
+            Some(RoutingError::NoRoute("05315000".to_owned()))
+        );
+        assert!(routing
+            .clone()
+            .with_default(endpoint("bund"))
+            .client_for("05315000")
+            .is_ok());
+        assert!(matches!(
+            routing.with_route("09", endpoint("bayern")),
+            Err(RoutingError::DuplicatePrefix(_))
+        ));
+        assert!(RoutingClient::new()
+            .with_route("9a", endpoint("x"))
+            .is_err());
+        Ok(())
+    }
+}
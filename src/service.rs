@@ -0,0 +1,198 @@
+//! OK.KOMM calls as a [`tower_service::Service`].
+//!
+//! [`OkKommService`] turns an [`OkKommCall`] into a SOAP envelope, hands the
+//! bytes to an inner HTTP service and decodes the answer into an
+//! [`OkKommResponse`]. The inner service defaults to [`TransportService`] and
+//! can be replaced by any `Service<Bytes, Response = TransportResponse>`, e.g.
+//! a stub in tests.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use anyhow::Error;
+use bytes::Bytes;
+use tower_service::Service;
+
+use crate::audit::Audit;
+use crate::config::Defaults;
+pub use crate::response::OkKommResponse;
+use crate::transport::{BoxFuture, Transport, TransportResponse};
+use crate::xml::WriteXml;
+use crate::zkoxml::AppsInfo;
+use crate::{decode_transport_response, finish_audit, soap_body};
+use crate::{Client, OkKommAktion};
+
+pub struct OkKommCall<T>
+where
+    T: WriteXml,
+{
+    pub aktion: OkKommAktion,
+    pub payload: T,
+    pub apps_info: Option<AppsInfo>,
+}
+
+impl<T> OkKommCall<T>
+where
+    T: WriteXml,
+{
+    pub fn new(aktion: OkKommAktion, payload: T, apps_info: Option<AppsInfo>) -> Self {
+        Self {
+            aktion,
+            payload,
+            apps_info,
+        }
+    }
+}
+
+/// Adapts the [`Transport`] of a [`Client`] to a tower service.
+#[derive(Clone)]
+pub struct TransportService {
+    transport: Arc<dyn Transport>,
+}
+
+impl TransportService {
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        Self { transport }
+    }
+}
+
+impl Service<Bytes> for TransportService {
+    type Response = TransportResponse;
+    type Error = Error;
+    type Future = BoxFuture<'static, TransportResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, body: Bytes) -> Self::Future {
+        let transport = self.transport.clone();
+        Box::pin(async move { transport.send(body).await })
+    }
+}
+
+#[derive(Clone)]
+pub struct OkKommService<S = TransportService> {
+    inner: S,
+    audit: Option<Audit>,
+    defaults: Defaults,
+}
+
+impl<S> OkKommService<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            audit: None,
+            defaults: Defaults::default(),
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl Client {
+    /// Tower service on top of the client's transport, sharing the audit sink
+    /// and configured defaults. The [`limits`](crate::limits),
+    /// [`breaker`](crate::breaker) and [`cache`](crate::cache) of the client
+    /// do not apply; use tower layers such as `ConcurrencyLimit`, `RateLimit`
+    /// or `Timeout` around the service instead.
+    pub fn service(&self) -> OkKommService<TransportService> {
+        OkKommService {
+            inner: TransportService::new(self.transport.clone()),
+            audit: self.audit.clone(),
+            defaults: self.defaults.clone(),
+        }
+    }
+}
+
+impl<S, T> Service<OkKommCall<T>> for OkKommService<S>
+where
+    S: Service<Bytes, Response = TransportResponse> + Clone + Send + 'static,
+    S::Error: Into<Error>,
+    S::Future: Send + 'static,
+    T: WriteXml,
+{
+    type Response = OkKommResponse;
+    type Error = Error;
+    type Future = BoxFuture<'static, OkKommResponse>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, call: OkKommCall<T>) -> Self::Future {
+        let audit = self.audit.clone();
+        let apps_info = self.defaults.apps_info(call.apps_info);
+        let audit_record = audit
+            .as_ref()
+            .map(|audit| audit.begin(&call.aktion, apps_info.as_ref(), &call.payload));
+        let body = soap_body(&self.defaults, call.aktion, call.payload, (), apps_info)
+            .and_then(|soap_request| soap_request.to_message());
+        // take the instance that was driven to readiness, leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let decoded = match body {
+                Ok(body) => decode_transport_response(inner.call(body).await.map_err(Into::into)),
+                Err(err) => Err(Error::from(err)),
+            };
+            finish_audit(audit.as_ref(), audit_record, &decoded);
+            let (info, daten) = decoded?;
+            Ok(OkKommResponse { info, daten })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use tower::ServiceExt;
+
+    use super::{OkKommCall, OkKommService};
+    use crate::okkomm::response_envelope;
+    use crate::transport::TransportResponse;
+    use crate::zkoxml::RawRequest;
+    use crate::OkKommAktion;
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Mandant {
+        #[serde(rename = "AGS")]
+        ags: String,
+    }
+
+    #[tokio::test]
+    async fn test_service_with_stub_transport(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let zkocxml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><ZKOCXML><XML_SYSTEM><SYSTEM><ANTWORT><ANT_TYP>OK</ANT_TYP></ANTWORT></SYSTEM></XML_SYSTEM><XML_DATEN><DATEN><MANDANT><AGS>09162000</AGS></MANDANT></DATEN></XML_DATEN></ZKOCXML>"#;
+        let soap = response_envelope(zkocxml.as_bytes());
+        let stub = tower::service_fn(move |request: Bytes| {
+            let soap = soap.clone();
+            async move {
+                assert!(String::from_utf8_lossy(&request).contains("okk:callApplicationByte"));
+                Ok::<_, anyhow::Error>(TransportResponse::new(200, soap))
+            }
+        });
+        let response = OkKommService::new(stub)
+            .oneshot(OkKommCall::new(
+                OkKommAktion::new(
+                    "EWO".to_owned(),
+                    "MANDANTENANFRAGE".to_owned(),
+                    "ABRUFEN".to_owned(),
+                    "09162000".to_owned(),
+                ),
+                RawRequest("<MANDANTENANFRAGE></MANDANTENANFRAGE>".to_owned()),
+                None,
+            ))
+            .await?;
+        assert!(response.error().is_none());
+        assert_eq!(
+            response.deserialize::<Mandant>()?,
+            Mandant {
+                ags: "09162000".to_owned()
+            }
+        );
+        Ok(())
+    }
+}
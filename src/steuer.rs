@@ -0,0 +1,5 @@
+//! Typed requests for the STEUER Verfahren (kommunale Steuern).
+
+pub mod hundesteuer;
+
+pub const VERFAHREN: &str = "STEUER";
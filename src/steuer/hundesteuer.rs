@@ -0,0 +1,363 @@
+//! STEUER HUNDESTEUER: An- and Abmeldung of a dog for the Hundesteuer.
+//!
+//! Sent with `OkKommAktion::new("STEUER", "HUNDESTEUER", <art>, <ziel_ags>)`,
+//! `<art>` being [`ANMELDUNG`] or [`ABMELDUNG`]. The Anmeldung carries the
+//! Halter as SUCHE and the dog as DATEN; the Abmeldung refers to the
+//! Kassenzeichen from the Anmeldung.
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person};
+use crate::steuer::VERFAHREN;
+use crate::validation::{self, Validate, ValidationError};
+use crate::xml::{betrag, datum, ja_nein, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, AppsInfo, Error};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "HUNDESTEUER";
+pub const ANMELDUNG: &str = "ANMELDUNG";
+pub const ABMELDUNG: &str = "ABMELDUNG";
+
+fn aktion(ausfuehrung: &str, ziel_ags: &str) -> OkKommAktion {
+    OkKommAktion::new(
+        VERFAHREN.to_owned(),
+        TYP.to_owned(),
+        ausfuehrung.to_owned(),
+        ziel_ags.to_owned(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Halter {
+    pub person: Person,
+    pub anschrift: Anschrift,
+}
+
+impl Validate for Halter {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.person.validate()?;
+        self.anschrift.validate()
+    }
+}
+
+impl WriteXml for Halter {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("HALTER").write_inner_content(|w| {
+            self.person.write_xml(w)?;
+            self.anschrift.write_xml(w)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hund {
+    pub name: String,
+    pub rasse: String,
+    /// Listenhund (gefährlicher Hund) by breed, taxed at a higher rate.
+    pub listenhund: bool,
+    pub wurfdatum: Option<NaiveDate>,
+    /// Transponder number of the microchip, 15 digits.
+    pub chipnummer: Option<String>,
+}
+
+impl Hund {
+    pub fn new(name: String, rasse: String) -> Self {
+        Self {
+            name,
+            rasse,
+            listenhund: false,
+            wurfdatum: None,
+            chipnummer: None,
+        }
+    }
+}
+
+impl Validate for Hund {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("HUNDENAME", &self.name)?;
+        validation::required("RASSE", &self.rasse)?;
+        if let Some(wurfdatum) = self.wurfdatum.as_ref() {
+            validation::not_in_future("WURFDATUM", wurfdatum)?;
+        }
+        match self.chipnummer.as_deref() {
+            Some(chip) if chip.len() != 15 || !chip.bytes().all(|b| b.is_ascii_digit()) => {
+                Err(validation::invalid("CHIPNUMMER", "expected fifteen digits"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl WriteXml for Hund {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("HUND").write_inner_content(|w| {
+            write_field_opt(w, "HUNDENAME", Some(self.name.as_str()))?;
+            write_field_opt(w, "RASSE", Some(self.rasse.as_str()))?;
+            write_field_opt(w, "LISTENHUND", Some(ja_nein::format(self.listenhund)))?;
+            write_field_opt(
+                w,
+                "WURFDATUM",
+                self.wurfdatum.as_ref().map(datum::format).as_deref(),
+            )?;
+            write_field_opt(w, "CHIPNUMMER", self.chipnummer.as_deref())?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hundeanmeldung {
+    pub halter: Halter,
+    pub hund: Hund,
+    /// Start of the Haltung in the municipality.
+    pub beginn: NaiveDate,
+}
+
+impl Validate for Hundeanmeldung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.halter.validate()?;
+        self.hund.validate()
+    }
+}
+
+struct AnmeldungDaten<'a>(&'a Hundeanmeldung);
+
+impl WriteXml for AnmeldungDaten<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("HUNDEANMELDUNG")
+            .write_inner_content(|w| {
+                self.0.hund.write_xml(w)?;
+                write_field_opt(w, "BEGINN", Some(datum::format(&self.0.beginn).as_str()))?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+/// Answer to an Anmeldung.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Hundesteuerbescheid {
+    #[serde(rename = "KASSENZEICHEN")]
+    pub kassenzeichen: String,
+    /// Number of the Steuermarke sent to the Halter.
+    #[serde(rename = "STEUERMARKE", default)]
+    pub steuermarke: Option<String>,
+    #[serde(
+        rename = "JAHRESBETRAG",
+        default,
+        deserialize_with = "betrag::option::deserialize"
+    )]
+    pub jahresbetrag_cent: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abmeldegrund {
+    Tod,
+    Abgabe,
+    Wegzug,
+    Sonstiger,
+}
+
+impl Abmeldegrund {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Abmeldegrund::Tod => "TOD",
+            Abmeldegrund::Abgabe => "ABGABE",
+            Abmeldegrund::Wegzug => "WEGZUG",
+            Abmeldegrund::Sonstiger => "SONSTIGER",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hundeabmeldung {
+    pub kassenzeichen: String,
+    pub steuermarke: Option<String>,
+    pub ende: NaiveDate,
+    pub grund: Abmeldegrund,
+}
+
+impl Validate for Hundeabmeldung {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validation::required("KASSENZEICHEN", &self.kassenzeichen)
+    }
+}
+
+struct AbmeldungSuche<'a>(&'a Hundeabmeldung);
+
+impl WriteXml for AbmeldungSuche<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element(TYP).write_inner_content(|w| {
+            write_field_opt(w, "KASSENZEICHEN", Some(self.0.kassenzeichen.as_str()))?;
+            write_field_opt(w, "STEUERMARKE", self.0.steuermarke.as_deref())?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+struct AbmeldungDaten<'a>(&'a Hundeabmeldung);
+
+impl WriteXml for AbmeldungDaten<'_> {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element("HUNDEABMELDUNG")
+            .write_inner_content(|w| {
+                write_field_opt(w, "ENDE", Some(datum::format(&self.0.ende).as_str()))?;
+                write_field_opt(w, "GRUND", Some(self.0.grund.as_str()))?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+}
+
+/// Answer to an Abmeldung.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Hundeabmeldebestaetigung {
+    #[serde(rename = "KASSENZEICHEN")]
+    pub kassenzeichen: String,
+    #[serde(
+        rename = "STEUERPFLICHT_ENDE",
+        default,
+        deserialize_with = "datum::option::deserialize"
+    )]
+    pub steuerpflicht_ende: Option<NaiveDate>,
+}
+
+impl Client {
+    pub async fn hundesteuer_anmelden(
+        &self,
+        ziel_ags: &str,
+        anmeldung: &Hundeanmeldung,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<Hundesteuerbescheid> {
+        anmeldung.validate()?;
+        self.call_uncached(
+            aktion(ANMELDUNG, ziel_ags),
+            &anmeldung.halter,
+            AnmeldungDaten(anmeldung),
+            apps_info,
+        )
+        .await?
+        .into_result()
+    }
+
+    pub async fn hundesteuer_abmelden(
+        &self,
+        ziel_ags: &str,
+        abmeldung: &Hundeabmeldung,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<Hundeabmeldebestaetigung> {
+        abmeldung.validate()?;
+        self.call_uncached(
+            aktion(ABMELDUNG, ziel_ags),
+            AbmeldungSuche(abmeldung),
+            AbmeldungDaten(abmeldung),
+            apps_info,
+        )
+        .await?
+        .into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{Abmeldegrund, Halter, Hund, Hundeabmeldung, Hundeanmeldung};
+    use crate::response::OkKommFehler;
+    use crate::testing;
+
+    #[tokio::test]
+    async fn test_hundesteuer_anmelden() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<HUNDESTEUERBESCHEID><KASSENZEICHEN>6.0815.4711</KASSENZEICHEN><STEUERMARKE>1234</STEUERMARKE><JAHRESBETRAG>1.200,00</JAHRESBETRAG></HUNDESTEUERBESCHEID>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_VERFAHREN>STEUER</AKT_VERFAHREN><AKT_TYP>HUNDESTEUER</AKT_TYP><AKT_AUSFUEHRUNG>ANMELDUNG</AKT_AUSFUEHRUNG>"));
+                assert!(request.contains("<SUCHE><HALTER><PERSON><NAME>Mustermann</NAME>"));
+                assert!(request.contains("<DATEN><HUNDEANMELDUNG><HUND><HUNDENAME>Bello</HUNDENAME><RASSE>Bullterrier</RASSE><LISTENHUND>J</LISTENHUND><CHIPNUMMER>276098106543210</CHIPNUMMER></HUND><BEGINN>01.04.2025</BEGINN></HUNDEANMELDUNG></DATEN>"));
+            },
+        );
+        let mut hund = Hund::new("Bello".to_owned(), "Bullterrier".to_owned());
+        hund.listenhund = true;
+        hund.chipnummer = Some("276098106543210".to_owned());
+        let mut anmeldung = Hundeanmeldung {
+            halter: Halter {
+                person: testing::person(),
+                anschrift: testing::anschrift(),
+            },
+            hund,
+            beginn: NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+        };
+        let bescheid = client
+            .hundesteuer_anmelden("05315000", &anmeldung, None)
+            .await?;
+        assert_eq!(bescheid.kassenzeichen, "6.0815.4711");
+        assert_eq!(bescheid.jahresbetrag_cent, Some(120_000));
+
+        anmeldung.hund.chipnummer = Some("4711".to_owned());
+        assert!(client
+            .hundesteuer_anmelden("05315000", &anmeldung, None)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hundesteuer_abmelden() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<HUNDEABMELDEBESTAETIGUNG><KASSENZEICHEN>6.0815.4711</KASSENZEICHEN><STEUERPFLICHT_ENDE>30.06.2025</STEUERPFLICHT_ENDE></HUNDEABMELDEBESTAETIGUNG>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_VERFAHREN>STEUER</AKT_VERFAHREN><AKT_TYP>HUNDESTEUER</AKT_TYP><AKT_AUSFUEHRUNG>ABMELDUNG</AKT_AUSFUEHRUNG><AKT_ZIEL_AGS>05315000</AKT_ZIEL_AGS>"));
+                assert!(request.contains("<SUCHE><HUNDESTEUER><KASSENZEICHEN>6.0815.4711</KASSENZEICHEN><STEUERMARKE>1234</STEUERMARKE></HUNDESTEUER></SUCHE>"));
+                assert!(request.contains("<DATEN><HUNDEABMELDUNG><ENDE>15.06.2025</ENDE><GRUND>TOD</GRUND></HUNDEABMELDUNG></DATEN>"));
+            },
+        );
+        let mut abmeldung = Hundeabmeldung {
+            kassenzeichen: "6.0815.4711".to_owned(),
+            steuermarke: Some("1234".to_owned()),
+            ende: NaiveDate::from_ymd_opt(2025, 6, 15).unwrap(),
+            grund: Abmeldegrund::Tod,
+        };
+        let bestaetigung = client
+            .hundesteuer_abmelden("05315000", &abmeldung, None)
+            .await?;
+        assert_eq!(bestaetigung.kassenzeichen, "6.0815.4711");
+        assert_eq!(
+            bestaetigung.steuerpflicht_ende,
+            NaiveDate::from_ymd_opt(2025, 6, 30)
+        );
+
+        abmeldung.kassenzeichen = String::new();
+        assert!(client
+            .hundesteuer_abmelden("05315000", &abmeldung, None)
+            .await
+            .is_err());
+
+        let client = testing::client(
+            testing::zkocxml(
+                &testing::fehler("KASSENZEICHEN_UNBEKANNT", "Kassenzeichen unbekannt"),
+                "",
+            ),
+            |_| {},
+        );
+        abmeldung.kassenzeichen = "6.0815.0000".to_owned();
+        let err = client
+            .hundesteuer_abmelden("05315000", &abmeldung, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OkKommFehler>().map(|f| f.typ.as_str()),
+            Some("KASSENZEICHEN_UNBEKANNT")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+//! Helpers for unit tests of the typed modules.
+
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person};
+use crate::okkomm::response_envelope;
+use crate::transport::{MemoryTransport, TransportResponse};
+use crate::Client;
+
+pub(crate) fn zkocxml(antwort: &str, daten: &str) -> String {
+    format!(
+        "<ZKOCXML><XML_SYSTEM><SYSTEM><ANTWORT>{antwort}</ANTWORT></SYSTEM></XML_SYSTEM><XML_DATEN><DATEN>{daten}</DATEN></XML_DATEN></ZKOCXML>"
+    )
+}
+
+/// Erika Mustermann, born 12.08.1964.
+pub(crate) fn person() -> Person {
+    Person::new(
+        "Mustermann".to_owned(),
+        "Erika".to_owned(),
+        NaiveDate::from_ymd_opt(1964, 8, 12).unwrap(),
+    )
+}
+
+/// Heidestraße 17, 51147 Köln.
+pub(crate) fn anschrift() -> Anschrift {
+    Anschrift::new(
+        "Heidestraße".to_owned(),
+        "17".to_owned(),
+        "51147".to_owned(),
+        "Köln".to_owned(),
+    )
+}
+
+pub(crate) fn fehler(typ: &str, text: &str) -> String {
+    format!(
+        "<FEHLER><FEH_TYP>{typ}</FEH_TYP><FEH_TEXT>{text}</FEH_TEXT><FEH_WERT></FEH_WERT><FEH_FELD></FEH_FELD></FEHLER>"
+    )
+}
+
+/// Client answering every request with `response` (a ZKOCXML document),
+/// after handing the decoded request ZKOCXML to `check`.
+pub(crate) fn client<F>(response: String, check: F) -> Client
+where
+    F: Fn(&str) + Send + Sync + 'static,
+{
+    Client::from_transport(MemoryTransport::new(move |body| {
+        let envelope =
+            crate::soap::SoapResponse::<crate::okkomm::OkKommCallApplicationByteRequest>::from_str(
+                &String::from_utf8_lossy(&body),
+            )?;
+        let request = envelope
+            .into_inner()
+            .and_then(|request| request.zkocxml().ok().flatten())
+            .unwrap_or_default();
+        check(&request);
+        Ok(TransportResponse::new(
+            200,
+            response_envelope(response.as_bytes()),
+        ))
+    }))
+}
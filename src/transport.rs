@@ -0,0 +1,174 @@
+//! Transport layer between the ZKOCXML/SOAP layer and the network.
+//!
+//! A [`Transport`] takes a complete SOAP envelope and returns the raw answer
+//! of the OK.KOMM endpoint. [`ReqwestTransport`] is used by
+//! [`Client::new`](crate::Client::new); other implementations (hyper, in-memory
+//! stubs, recorders) can be plugged in with
+//! [`Client::from_transport`](crate::Client::from_transport).
+
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::Error;
+use bytes::Bytes;
+
+use crate::redact;
+use crate::tls::{self, ClientIdentity};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = anyhow::Result<T>> + Send + 'a>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportResponse {
+    pub status: u16,
+    pub body: Bytes,
+}
+
+impl TransportResponse {
+    pub fn new(status: u16, body: impl Into<Bytes>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+pub trait Transport: Send + Sync {
+    /// Sends a SOAP envelope and returns the answer of the endpoint.
+    ///
+    /// An `Err` means that no answer was received at all; HTTP error
+    /// statuses are reported through [`TransportResponse::status`].
+    fn send(&self, body: Bytes) -> BoxFuture<'_, TransportResponse>;
+
+    /// URL of the endpoint, if the transport posts to one.
+    fn url(&self) -> Option<&str> {
+        None
+    }
+}
+
+pub(crate) fn default_headers() -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        "Content-Type",
+        reqwest::header::HeaderValue::from_static("text/xml; charset=utf-8"),
+    );
+    headers
+}
+
+/// `reqwest` client builder with the crate's headers and TLS settings and
+/// without system proxies.
+pub(crate) fn client_builder(
+    tls_root_certificates: Option<Vec<Vec<u8>>>,
+    identity: Option<ClientIdentity>,
+) -> anyhow::Result<reqwest::ClientBuilder> {
+    let mut client_builder = reqwest::ClientBuilder::new()
+        .no_proxy()
+        .default_headers(default_headers());
+
+    #[cfg(feature = "rustls-tls")]
+    {
+        client_builder = client_builder.use_rustls_tls();
+    }
+
+    // without a TLS backend only the `compile_error!` in `tls` is reported
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    {
+        for cert in tls::root_certificates(tls_root_certificates) {
+            client_builder = client_builder.add_root_certificate(cert);
+        }
+
+        if let Some(identity) = identity {
+            client_builder = client_builder.identity(identity.to_reqwest()?);
+        }
+    }
+
+    Ok(client_builder)
+}
+
+/// Posts SOAP envelopes to a single OK.KOMM endpoint with `reqwest`.
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    pub url: String,
+}
+
+impl ReqwestTransport {
+    pub fn new(
+        url: String,
+        tls_root_certificates: Option<Vec<Vec<u8>>>,
+        identity: Option<ClientIdentity>,
+    ) -> anyhow::Result<Self> {
+        let client = client_builder(tls_root_certificates, identity)?.build()?;
+        Ok(Self::from_client(client, url))
+    }
+
+    pub fn from_client(client: reqwest::Client, url: String) -> Self {
+        Self { client, url }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, body: Bytes) -> BoxFuture<'_, TransportResponse> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .post(&self.url)
+                .body(body)
+                .send()
+                .await
+                .map_err(|err| {
+                    Error::msg(redact::text(&format!(
+                        "Error while sending SOAP request to OK.KOMM: {err:#?}"
+                    )))
+                })?;
+            let status = response.status().as_u16();
+            let body = response.bytes().await.map_err(|err| {
+                Error::msg(redact::text(&format!(
+                    "Error while receiving SOAP response from OK.KOMM: {err:#?}"
+                )))
+            })?;
+            Ok(TransportResponse { status, body })
+        })
+    }
+
+    fn url(&self) -> Option<&str> {
+        Some(&self.url)
+    }
+}
+
+/// In-memory transport answering every request with `F`, e.g. for unit tests.
+pub struct MemoryTransport<F> {
+    handler: F,
+}
+
+impl<F> MemoryTransport<F>
+where
+    F: Fn(Bytes) -> anyhow::Result<TransportResponse> + Send + Sync,
+{
+    pub fn new(handler: F) -> Self {
+        Self { handler }
+    }
+}
+
+impl<F> Transport for MemoryTransport<F>
+where
+    F: Fn(Bytes) -> anyhow::Result<TransportResponse> + Send + Sync,
+{
+    fn send(&self, body: Bytes) -> BoxFuture<'_, TransportResponse> {
+        let response = (self.handler)(body);
+        Box::pin(async move { response })
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<F> crate::blocking::Transport for MemoryTransport<F>
+where
+    F: Fn(Bytes) -> anyhow::Result<TransportResponse> + Send + Sync,
+{
+    fn send(&self, body: Bytes) -> anyhow::Result<TransportResponse> {
+        (self.handler)(body)
+    }
+}
@@ -0,0 +1,69 @@
+//! Checks of typed requests before they are sent, so that missing or
+//! malformed fields do not cost a FEHLER round-trip.
+//!
+//! Errors name the affected ZKOCXML element but never its value, the values
+//! are usually personal data.
+
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Europe::Berlin;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ValidationError {
+    #[error("required field {0} is missing")]
+    Missing(&'static str),
+
+    #[error("invalid value in field {field}: {reason}")]
+    Invalid {
+        field: &'static str,
+        reason: &'static str,
+    },
+}
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+impl<T> Validate for [T]
+where
+    T: Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.iter().try_for_each(Validate::validate)
+    }
+}
+
+pub(crate) fn required(field: &'static str, value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::Missing(field));
+    }
+    Ok(())
+}
+
+pub(crate) fn invalid(field: &'static str, reason: &'static str) -> ValidationError {
+    ValidationError::Invalid { field, reason }
+}
+
+/// German Postleitzahl, five digits.
+pub(crate) fn plz(field: &'static str, value: &str) -> Result<(), ValidationError> {
+    required(field, value)?;
+    if value.len() != 5 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(field, "expected five digits"));
+    }
+    Ok(())
+}
+
+/// Amtlicher Gemeindeschlüssel, eight digits.
+pub(crate) fn ags(field: &'static str, value: &str) -> Result<(), ValidationError> {
+    required(field, value)?;
+    if value.len() != 8 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(field, "expected eight digits"));
+    }
+    Ok(())
+}
+
+pub(crate) fn not_in_future(field: &'static str, value: &NaiveDate) -> Result<(), ValidationError> {
+    if *value > Utc::now().with_timezone(&Berlin).date_naive() {
+        return Err(invalid(field, "date lies in the future"));
+    }
+    Ok(())
+}
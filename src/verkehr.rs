@@ -0,0 +1,5 @@
+//! Typed requests for the VERKEHR Verfahren (Straßenverkehrsbehörde).
+
+pub mod bewohnerparken;
+
+pub const VERFAHREN: &str = "VERKEHR";
@@ -0,0 +1,249 @@
+//! VERKEHR BEWOHNERPARKAUSWEIS: application and renewal of a
+//! Bewohnerparkausweis.
+//!
+//! Sent with `OkKommAktion::new("VERKEHR", "BEWOHNERPARKAUSWEIS", <art>,
+//! <ziel_ags>)`, `<art>` being [`BEANTRAGEN`] or [`VERLAENGERN`]. The
+//! Straßenverkehrsbehörde checks the Meldedaten and the Kfz data and answers
+//! with the issued [`Bewohnerparkausweis`].
+
+use chrono::NaiveDate;
+
+use crate::ewo::{Anschrift, Person};
+use crate::redact;
+use crate::validation::{self, Validate, ValidationError};
+use crate::verkehr::VERFAHREN;
+use crate::xml::{betrag, datum, ja_nein, WriteXml, XmlWriter};
+use crate::zkoxml::{write_field_opt, AppsInfo, Error};
+use crate::{Client, OkKommAktion};
+
+pub const TYP: &str = "BEWOHNERPARKAUSWEIS";
+pub const BEANTRAGEN: &str = "BEANTRAGEN";
+pub const VERLAENGERN: &str = "VERLAENGERN";
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct BewohnerparkausweisAntrag {
+    pub bewohner: Person,
+    pub anschrift: Anschrift,
+    /// Parkzone, e.g. `A` or `M-12`.
+    pub zone: String,
+    /// Amtliches Kennzeichen, e.g. `K-AB 1234`.
+    pub kennzeichen: String,
+    /// The applicant is Halter of the vehicle, otherwise e.g. a company car.
+    pub halter: bool,
+    pub gueltig_ab: NaiveDate,
+    pub gueltig_bis: NaiveDate,
+    /// Number of the expiring Ausweis, required for a renewal.
+    pub bisherige_ausweisnummer: Option<String>,
+}
+
+impl BewohnerparkausweisAntrag {
+    fn ausfuehrung(&self) -> &'static str {
+        match self.bisherige_ausweisnummer {
+            Some(_) => VERLAENGERN,
+            None => BEANTRAGEN,
+        }
+    }
+}
+
+impl std::fmt::Debug for BewohnerparkausweisAntrag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BewohnerparkausweisAntrag")
+            .field("bewohner", &self.bewohner)
+            .field("anschrift", &self.anschrift)
+            .field("zone", &self.zone)
+            .field(
+                "kennzeichen",
+                &redact::value("KENNZEICHEN", &self.kennzeichen),
+            )
+            .field("halter", &self.halter)
+            .field("gueltig_ab", &self.gueltig_ab)
+            .field("gueltig_bis", &self.gueltig_bis)
+            .field("bisherige_ausweisnummer", &self.bisherige_ausweisnummer)
+            .finish()
+    }
+}
+
+/// Accepts `K-AB 1234`, `K AB 1234` and `BGL-A 1E`: Unterscheidungszeichen,
+/// Erkennungsbuchstaben and number, optionally with an E or H suffix.
+fn kennzeichen(field: &'static str, value: &str) -> Result<(), ValidationError> {
+    validation::required(field, value)?;
+    let invalid = || validation::invalid(field, "expected e.g. K-AB 1234");
+    let value = value.trim().to_uppercase();
+    let (ort, rest) = value.split_once(['-', ' ']).ok_or_else(invalid)?;
+    let (buchstaben, nummer) = rest.trim().split_once(' ').ok_or_else(invalid)?;
+    let nummer = nummer.trim_end_matches(['E', 'H']);
+    let letters = |s: &str, max| {
+        (1..=max).contains(&s.chars().count()) && s.chars().all(|c| c.is_alphabetic())
+    };
+    if !letters(ort, 3)
+        || !letters(buchstaben, 2)
+        || !(1..=4).contains(&nummer.len())
+        || !nummer.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+impl Validate for BewohnerparkausweisAntrag {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.bewohner.validate()?;
+        self.anschrift.validate()?;
+        validation::required("ZONE", &self.zone)?;
+        kennzeichen("KENNZEICHEN", &self.kennzeichen)?;
+        if self.gueltig_bis <= self.gueltig_ab {
+            return Err(validation::invalid(
+                "GUELTIG_BIS",
+                "validity ends before it starts",
+            ));
+        }
+        if let Some(nummer) = self.bisherige_ausweisnummer.as_deref() {
+            validation::required("BISHERIGE_AUSWEISNUMMER", nummer)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteXml for BewohnerparkausweisAntrag {
+    fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
+        w.create_element(TYP).write_inner_content(|w| {
+            self.bewohner.write_xml(w)?;
+            self.anschrift.write_xml(w)?;
+            write_field_opt(w, "ZONE", Some(self.zone.as_str()))?;
+            w.create_element("FAHRZEUG").write_inner_content(|w| {
+                write_field_opt(
+                    w,
+                    "KENNZEICHEN",
+                    Some(self.kennzeichen.trim().to_uppercase().as_str()),
+                )?;
+                write_field_opt(w, "HALTER", Some(ja_nein::format(self.halter)))?;
+                Ok(())
+            })?;
+            write_field_opt(
+                w,
+                "GUELTIG_AB",
+                Some(datum::format(&self.gueltig_ab).as_str()),
+            )?;
+            write_field_opt(
+                w,
+                "GUELTIG_BIS",
+                Some(datum::format(&self.gueltig_bis).as_str()),
+            )?;
+            write_field_opt(
+                w,
+                "BISHERIGE_AUSWEISNUMMER",
+                self.bisherige_ausweisnummer.as_deref(),
+            )?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct Bewohnerparkausweis {
+    #[serde(rename = "AUSWEISNUMMER")]
+    pub ausweisnummer: String,
+    #[serde(rename = "ZONE")]
+    pub zone: String,
+    #[serde(rename = "GUELTIG_AB", deserialize_with = "datum::deserialize")]
+    pub gueltig_ab: NaiveDate,
+    #[serde(rename = "GUELTIG_BIS", deserialize_with = "datum::deserialize")]
+    pub gueltig_bis: NaiveDate,
+    #[serde(
+        rename = "GEBUEHR",
+        default,
+        deserialize_with = "betrag::option::deserialize"
+    )]
+    pub gebuehr_cent: Option<u64>,
+}
+
+impl Client {
+    /// Applies for a Bewohnerparkausweis at `ziel_ags`, or renews one if
+    /// [`BewohnerparkausweisAntrag::bisherige_ausweisnummer`] is set.
+    ///
+    /// Unlike [`Client::send_request_xml`] a FEHLER of the
+    /// Straßenverkehrsbehörde is returned as
+    /// [`OkKommFehler`](crate::response::OkKommFehler), not as a
+    /// deserialization error, and the answer never comes from the
+    /// [`cache`](crate::cache).
+    pub async fn bewohnerparkausweis(
+        &self,
+        ziel_ags: &str,
+        antrag: &BewohnerparkausweisAntrag,
+        apps_info: Option<AppsInfo>,
+    ) -> anyhow::Result<Bewohnerparkausweis> {
+        antrag.validate()?;
+        let aktion = OkKommAktion::new(
+            VERFAHREN.to_owned(),
+            TYP.to_owned(),
+            antrag.ausfuehrung().to_owned(),
+            ziel_ags.to_owned(),
+        );
+        self.call_uncached(aktion, antrag, (), apps_info)
+            .await?
+            .into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{kennzeichen, BewohnerparkausweisAntrag};
+    use crate::response::OkKommFehler;
+    use crate::testing;
+
+    #[tokio::test]
+    async fn test_bewohnerparkausweis() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = testing::client(
+            testing::zkocxml(
+                "",
+                "<BEWOHNERPARKAUSWEIS><AUSWEISNUMMER>BP-2025-123</AUSWEISNUMMER><ZONE>M-12</ZONE><GUELTIG_AB>01.04.2025</GUELTIG_AB><GUELTIG_BIS>31.03.2026</GUELTIG_BIS><GEBUEHR>30,00</GEBUEHR></BEWOHNERPARKAUSWEIS>",
+            ),
+            |request| {
+                assert!(request.contains("<AKT_VERFAHREN>VERKEHR</AKT_VERFAHREN><AKT_TYP>BEWOHNERPARKAUSWEIS</AKT_TYP><AKT_AUSFUEHRUNG>VERLAENGERN</AKT_AUSFUEHRUNG>"));
+                assert!(request.contains("<ZONE>M-12</ZONE><FAHRZEUG><KENNZEICHEN>M-AB 1234E</KENNZEICHEN><HALTER>J</HALTER></FAHRZEUG>"));
+                assert!(request.contains("<BISHERIGE_AUSWEISNUMMER>BP-2024-77</BISHERIGE_AUSWEISNUMMER>"));
+            },
+        );
+        let mut antrag = BewohnerparkausweisAntrag {
+            bewohner: testing::person(),
+            anschrift: testing::anschrift(),
+            zone: "M-12".to_owned(),
+            kennzeichen: "m-ab 1234e".to_owned(),
+            halter: true,
+            gueltig_ab: NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            gueltig_bis: NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            bisherige_ausweisnummer: Some("BP-2024-77".to_owned()),
+        };
+        let ausweis = client
+            .bewohnerparkausweis("09162000", &antrag, None)
+            .await?;
+        assert_eq!(ausweis.ausweisnummer, "BP-2025-123");
+        assert_eq!(ausweis.gebuehr_cent, Some(3000));
+        assert!(!format!("{antrag:?}").contains("1234"));
+
+        assert!(kennzeichen("KENNZEICHEN", "BGL A 1").is_ok());
+        antrag.kennzeichen = "M-1234".to_owned();
+        assert!(client
+            .bewohnerparkausweis("09162000", &antrag, None)
+            .await
+            .is_err());
+
+        let client = testing::client(
+            testing::zkocxml(&testing::fehler("ZONE_UNBEKANNT", "Zone unbekannt"), ""),
+            |_| {},
+        );
+        antrag.kennzeichen = "M-AB 1234E".to_owned();
+        let err = client
+            .bewohnerparkausweis("09162000", &antrag, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OkKommFehler>().map(|f| f.typ.as_str()),
+            Some("ZONE_UNBEKANNT")
+        );
+        Ok(())
+    }
+}
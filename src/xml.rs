@@ -1,7 +1,182 @@
-use bytes::{buf::Writer, BytesMut};
+use bytes::{buf::Writer, BufMut, BytesMut};
 
 pub type XmlWriter = quick_xml::Writer<Writer<BytesMut>>;
 
 pub trait WriteXml {
     fn write_xml(&self, writer: &mut XmlWriter) -> Result<(), quick_xml::Error>;
 }
+
+impl<T> WriteXml for &T
+where
+    T: WriteXml + ?Sized,
+{
+    fn write_xml(&self, writer: &mut XmlWriter) -> Result<(), quick_xml::Error> {
+        (**self).write_xml(writer)
+    }
+}
+
+pub fn to_bytes<T>(value: &T) -> Result<bytes::Bytes, quick_xml::Error>
+where
+    T: WriteXml + ?Sized,
+{
+    let mut writer = quick_xml::Writer::new(BytesMut::new().writer());
+    value.write_xml(&mut writer)?;
+    Ok(writer.into_inner().into_inner().freeze())
+}
+
+/// Re-serializes `xml` without comments and whitespace between elements.
+pub fn normalize(xml: &str) -> Result<String, quick_xml::Error> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    loop {
+        match reader.read_event()? {
+            quick_xml::events::Event::Eof => break,
+            quick_xml::events::Event::Comment(_) => {}
+            event => writer.write_event(event)?,
+        }
+    }
+    Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+/// Dates as used by OK.KOMM, e.g. `24.01.2023`.
+pub mod datum {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Deserializer};
+
+    pub const FORMAT: &str = "%d.%m.%Y";
+
+    pub fn format(date: &NaiveDate) -> String {
+        date.format(FORMAT).to_string()
+    }
+
+    pub fn parse(value: &str) -> Result<NaiveDate, chrono::ParseError> {
+        NaiveDate::parse_from_str(value.trim(), FORMAT)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        parse(&value).map_err(serde::de::Error::custom)
+    }
+
+    /// Like [`deserialize`], but an empty element is `None`.
+    pub mod option {
+        use chrono::NaiveDate;
+        use serde::{Deserialize, Deserializer};
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(value) if !value.trim().is_empty() => super::parse(&value)
+                    .map(Some)
+                    .map_err(serde::de::Error::custom),
+                _ => Ok(None),
+            }
+        }
+    }
+}
+
+/// Flags as used by OK.KOMM, `J` (ja) or `N` (nein).
+pub mod ja_nein {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn format(value: bool) -> &'static str {
+        if value {
+            "J"
+        } else {
+            "N"
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<bool, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+        match value.trim() {
+            "J" | "j" | "1" | "true" => Ok(true),
+            "N" | "n" | "0" | "false" | "" => Ok(false),
+            other => Err(serde::de::Error::custom(format!(
+                "expected J or N, found {other:?}"
+            ))),
+        }
+    }
+}
+
+/// Amounts in euro as used by OK.KOMM, e.g. `1.200,00`, `1.200` or
+/// `1200.00`, read as cents.
+pub mod betrag {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn parse(value: &str) -> Option<u64> {
+        let value = value.trim();
+        // `1.200,00` and `1.200` use `.` to group thousands, `1200.00` as
+        // decimal point
+        let thousands = value.contains(',')
+            || value
+                .rsplit_once('.')
+                .is_some_and(|(_, group)| group.len() == 3);
+        let value = match thousands {
+            true => value.replace('.', ""),
+            false => value.replace('.', ","),
+        };
+        let (euro, cent) = value.split_once(',').unwrap_or((&value, "0"));
+        let cent = match cent.len() {
+            1 => cent.parse::<u64>().ok()? * 10,
+            2 => cent.parse::<u64>().ok()?,
+            _ => return None,
+        };
+        euro.parse::<u64>()
+            .ok()?
+            .checked_mul(100)?
+            .checked_add(cent)
+    }
+
+    pub fn format(cent: u64) -> String {
+        format!("{},{:02}", cent / 100, cent % 100)
+    }
+
+    /// Like [`parse`], an empty element is `None`.
+    pub mod option {
+        use super::*;
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(value) if !value.trim().is_empty() => super::parse(&value)
+                    .map(Some)
+                    .ok_or_else(|| serde::de::Error::custom(format!("invalid amount {value:?}"))),
+                _ => Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::betrag;
+
+    #[test]
+    fn test_betrag() {
+        assert_eq!(betrag::parse("120,00"), Some(12000));
+        assert_eq!(betrag::parse("120.5"), Some(12050));
+        assert_eq!(betrag::parse("1.200,00"), Some(120000));
+        assert_eq!(betrag::parse("1.200"), Some(120000));
+        assert_eq!(betrag::parse("1.200.000"), Some(120000000));
+        assert_eq!(betrag::parse(" 30 "), Some(3000));
+        assert_eq!(betrag::parse("1,234"), None);
+        assert_eq!(betrag::parse("1.200.50"), None);
+        assert_eq!(betrag::parse("abc"), None);
+        assert_eq!(betrag::parse(&u64::MAX.to_string()), None);
+        assert_eq!(betrag::parse("184467440737095516,15"), Some(u64::MAX));
+        assert_eq!(betrag::parse("184467440737095516,16"), None);
+        assert_eq!(betrag::format(120050), "1200,50");
+    }
+}
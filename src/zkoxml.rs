@@ -11,9 +11,10 @@ pub use quick_xml::{
     Error, Writer,
 };
 
+use crate::redact;
 use crate::xml::{WriteXml, XmlWriter};
 
-fn write_field_opt<W>(
+pub(crate) fn write_field_opt<W>(
     w: &mut Writer<W>,
     name: &'static str,
     value: Option<&str>,
@@ -28,7 +29,7 @@ where
     Ok(())
 }
 
-#[derive(Debug, Clone, serde::Deserialize, PartialEq)]
+#[derive(Clone, serde::Deserialize, PartialEq)]
 pub struct AppsInfo {
     #[serde(rename = "APPS_TYP")]
     pub typ: Option<String>,
@@ -56,6 +57,54 @@ pub struct AppsInfo {
     pub return_queue: Option<String>,
 }
 
+impl AppsInfo {
+    /// APPS_INFO sent when the caller passes none, stamped with `now`.
+    pub fn default_at(now: &DateTime<Tz>) -> Self {
+        Self {
+            typ: Some("DGS".to_owned()),
+            name: Some("Digital Gov as a Service".to_owned()),
+            version: Some("2023.4.0".to_owned()),
+            ags: Some(String::default()),
+            datum: Some(now.format("%d.%m.%Y").to_string()),
+            uhrzeit: Some(now.format("%H:%M:%S").to_string()),
+            request_id: Some(String::default()),
+            source_id: Some(String::default()),
+            kennung: Some(String::default()),
+            ip_adresse: Some(String::default()),
+            ziel_url: Some(String::default()),
+            return_queue: Some(String::default()),
+        }
+    }
+}
+
+impl std::fmt::Debug for AppsInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppsInfo")
+            .field("typ", &redact::opt("APPS_TYP", &self.typ))
+            .field("name", &redact::opt("APPS_NAME", &self.name))
+            .field("version", &redact::opt("APPS_VERSION", &self.version))
+            .field("ags", &redact::opt("APPS_AGS", &self.ags))
+            .field("datum", &redact::opt("APPS_DATUM", &self.datum))
+            .field("uhrzeit", &redact::opt("APPS_UHRZEIT", &self.uhrzeit))
+            .field(
+                "request_id",
+                &redact::opt("APPS_REQUEST_ID", &self.request_id),
+            )
+            .field("source_id", &redact::opt("APPS_SOURCE_ID", &self.source_id))
+            .field("kennung", &redact::opt("APPS_KENNUNG", &self.kennung))
+            .field(
+                "ip_adresse",
+                &redact::opt("APPS_IP_ADRESSE", &self.ip_adresse),
+            )
+            .field("ziel_url", &redact::opt("APPS_ZIEL_URL", &self.ziel_url))
+            .field(
+                "return_queue",
+                &redact::opt("APPS_RETURN_QUEUE", &self.return_queue),
+            )
+            .finish()
+    }
+}
+
 impl WriteXml for AppsInfo {
     fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
         w.create_element("APPS_INFO").write_inner_content(|w| {
@@ -77,7 +126,7 @@ impl WriteXml for AppsInfo {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, PartialEq)]
+#[derive(Clone, serde::Deserialize, PartialEq)]
 pub struct Fehler {
     #[serde(rename = "FEH_TYP")]
     pub typ: Option<String>,
@@ -89,6 +138,17 @@ pub struct Fehler {
     pub feld: Option<String>,
 }
 
+impl std::fmt::Debug for Fehler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fehler")
+            .field("typ", &redact::opt("FEH_TYP", &self.typ))
+            .field("text", &redact::opt("FEH_TEXT", &self.text))
+            .field("wert", &redact::opt("FEH_WERT", &self.wert))
+            .field("feld", &redact::opt("FEH_FELD", &self.feld))
+            .finish()
+    }
+}
+
 impl WriteXml for Fehler {
     fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
         w.create_element("FEHLER").write_inner_content(|w| {
@@ -102,7 +162,7 @@ impl WriteXml for Fehler {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, PartialEq)]
+#[derive(Clone, Default, serde::Deserialize, PartialEq)]
 pub struct Aktion {
     #[serde(rename = "AKT_VERFAHREN")]
     pub verfahren: Option<String>,
@@ -114,6 +174,20 @@ pub struct Aktion {
     pub ziel_ags: Option<String>,
 }
 
+impl std::fmt::Debug for Aktion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Aktion")
+            .field("verfahren", &redact::opt("AKT_VERFAHREN", &self.verfahren))
+            .field("typ", &redact::opt("AKT_TYP", &self.typ))
+            .field(
+                "ausfuehrung",
+                &redact::opt("AKT_AUSFUEHRUNG", &self.ausfuehrung),
+            )
+            .field("ziel_ags", &redact::opt("AKT_ZIEL_AGS", &self.ziel_ags))
+            .finish()
+    }
+}
+
 impl WriteXml for Aktion {
     fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
         w.create_element("AKTION").write_inner_content(|w| {
@@ -127,7 +201,7 @@ impl WriteXml for Aktion {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, PartialEq)]
+#[derive(Clone, serde::Deserialize, PartialEq)]
 pub struct Login {
     #[serde(rename = "AKT_TECHUSER")]
     pub techuser: Option<String>,
@@ -135,6 +209,15 @@ pub struct Login {
     pub techpwd: Option<String>,
 }
 
+impl std::fmt::Debug for Login {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Login")
+            .field("techuser", &redact::opt("AKT_TECHUSER", &self.techuser))
+            .field("techpwd", &redact::opt("AKT_TECHPWD", &self.techpwd))
+            .finish()
+    }
+}
+
 impl WriteXml for Login {
     fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
         w.create_element("AKT_LOGIN").write_inner_content(|w| {
@@ -146,7 +229,7 @@ impl WriteXml for Login {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, PartialEq)]
+#[derive(Clone, serde::Deserialize, PartialEq)]
 pub struct Antwort {
     #[serde(rename = "ANT_TYP")]
     pub typ: Option<String>,
@@ -162,6 +245,19 @@ pub struct Antwort {
     pub fehler: Option<Fehler>,
 }
 
+impl std::fmt::Debug for Antwort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Antwort")
+            .field("typ", &redact::opt("ANT_TYP", &self.typ))
+            .field("apps", &redact::opt("ANT_APPS", &self.apps))
+            .field("struktur", &redact::opt("ANT_STRUKTUR", &self.struktur))
+            .field("datum", &redact::opt("ANT_DATUM", &self.datum))
+            .field("uhrzeit", &redact::opt("ANT_UHRZEIT", &self.uhrzeit))
+            .field("fehler", &self.fehler)
+            .finish()
+    }
+}
+
 impl WriteXml for Antwort {
     fn write_xml(&self, w: &mut XmlWriter) -> Result<(), Error> {
         w.create_element("ANTWORT").write_inner_content(|w| {
@@ -286,7 +382,7 @@ impl ZkocxmlInfo {
         Ok(())
     }
 
-    pub fn error(&self) -> Option<ZkocxmlError> {
+    pub fn error(&self) -> Option<ZkocxmlError<'_>> {
         self.xml_system
             .system
             .antwort
@@ -310,7 +406,6 @@ impl ZkocxmlInfo {
     }
 }
 
-#[derive(Debug)]
 pub struct ZkocxmlError<'a> {
     pub typ: &'a str,
     pub text: &'a str,
@@ -318,6 +413,17 @@ pub struct ZkocxmlError<'a> {
     pub feld: &'a str,
 }
 
+impl std::fmt::Debug for ZkocxmlError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZkocxmlError")
+            .field("typ", &redact::value("FEH_TYP", self.typ))
+            .field("text", &redact::value("FEH_TEXT", self.text))
+            .field("wert", &redact::value("FEH_WERT", self.wert))
+            .field("feld", &redact::value("FEH_FELD", self.feld))
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Request<R, D = ()>
 where
@@ -341,10 +447,6 @@ where
     D: WriteXml,
 {
     pub fn new(request: impl Into<Option<R>>, apps_info: Option<AppsInfo>) -> Self {
-        let now: DateTime<Tz> = Utc::now().with_timezone(&Berlin);
-        let date = now.format("%d.%m.%Y").to_string();
-        let time = now.format("%H:%M:%S").to_string();
-
         Self {
             info: ZkocxmlInfo {
                 xml_system: XmlSystem {
@@ -367,19 +469,8 @@ where
                             uhrzeit: None,
                             fehler: None,
                         }),
-                        apps_info: Some(apps_info.unwrap_or(AppsInfo {
-                            typ: Some("DGS".to_owned()),
-                            name: Some("Digital Gov as a Service".to_owned()),
-                            version: Some("2023.4.0".to_owned()),
-                            ags: Some(String::default()),
-                            datum: Some(date),
-                            uhrzeit: Some(time),
-                            request_id: Some(String::default()),
-                            source_id: Some(String::default()),
-                            kennung: Some(String::default()),
-                            ip_adresse: Some(String::default()),
-                            ziel_url: Some(String::default()),
-                            return_queue: Some(String::default()),
+                        apps_info: Some(apps_info.unwrap_or_else(|| {
+                            AppsInfo::default_at(&Utc::now().with_timezone(&Berlin))
                         })),
                     },
                 },
@@ -429,6 +520,14 @@ where
         self
     }
 
+    pub fn with_login<S: ToString>(mut self, techuser: S, techpwd: S) -> Self {
+        self.info.xml_system.system.akt_login = Some(Login {
+            techuser: Some(techuser.to_string()),
+            techpwd: Some(techpwd.to_string()),
+        });
+        self
+    }
+
     pub fn with_xml_daten(mut self, data: impl Into<Option<D>>) -> Self {
         self.data = data.into();
         self
@@ -477,17 +576,40 @@ impl WriteXml for BytesRequest {
     }
 }
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct ContentContainerMessage {
     pub content_type: String,
     pub ref_id: String,
     pub content: String,
 }
+
+impl std::fmt::Debug for ContentContainerMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentContainerMessage")
+            .field("content_type", &self.content_type)
+            .field("ref_id", &self.ref_id)
+            .field("content", &redact::Xml(&self.content))
+            .finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
 pub struct ContentContainerAttachment {
     pub content_type: String,
     pub ref_id: String,
     pub content: Bytes,
 }
 
+impl std::fmt::Debug for ContentContainerAttachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentContainerAttachment")
+            .field("content_type", &self.content_type)
+            .field("ref_id", &self.ref_id)
+            .field("content", &format_args!("{} bytes", self.content.len()))
+            .finish()
+    }
+}
+
 pub struct ContentContainer<'m> {
     pub messages: &'m Vec<ContentContainerMessage>,
     pub attachments: &'m Vec<ContentContainerAttachment>,
@@ -547,6 +669,94 @@ impl WriteXml for ContentContainer<'_> {
     }
 }
 
+/// Decoded `OK_KOMM_CONTENTCONTAINER` of an answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentContainerResponse {
+    pub messages: Vec<ContentContainerMessage>,
+    pub attachments: Vec<ContentContainerAttachment>,
+}
+
+impl ContentContainerResponse {
+    /// Reads the first `OK_KOMM_CONTENTCONTAINER` in `xml`, `None` if there is
+    /// none.
+    pub fn parse(xml: &str) -> Result<Option<Self>, crate::okkomm::Error> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut container: Option<Self> = None;
+        // contentType, refId and base64 content of the current MESSAGE or ATTACHMENT
+        let mut part: Option<(String, String, String)> = None;
+        loop {
+            match reader.read_event()? {
+                Event::Eof => break,
+                Event::Start(e) if e.local_name().as_ref() == b"OK_KOMM_CONTENTCONTAINER" => {
+                    container.get_or_insert_with(Self::default);
+                }
+                Event::Start(e)
+                    if container.is_some()
+                        && matches!(e.local_name().as_ref(), b"MESSAGE" | b"ATTACHMENT") =>
+                {
+                    let mut content_type = String::new();
+                    let mut ref_id = String::new();
+                    for attribute in e.attributes() {
+                        let attribute = attribute.map_err(Error::from)?;
+                        let value = attribute.unescape_value()?.into_owned();
+                        match attribute.key.local_name().as_ref() {
+                            b"contentType" => content_type = value,
+                            b"refId" => ref_id = value,
+                            _ => {}
+                        }
+                    }
+                    part = Some((content_type, ref_id, String::new()));
+                }
+                Event::CData(e) => {
+                    if let Some((_, _, content)) = part.as_mut() {
+                        content.push_str(&String::from_utf8_lossy(&e.into_inner()));
+                    }
+                }
+                Event::Text(e) => {
+                    if let Some((_, _, content)) = part.as_mut() {
+                        content.push_str(&e.unescape()?);
+                    }
+                }
+                Event::End(e) => match (e.local_name().as_ref(), container.as_mut()) {
+                    (b"MESSAGE", Some(container)) => {
+                        if let Some((content_type, ref_id, content)) = part.take() {
+                            let content =
+                                base64::engine::general_purpose::STANDARD.decode(content.trim())?;
+                            container.messages.push(ContentContainerMessage {
+                                content_type,
+                                ref_id,
+                                content: String::from_utf8(content)?,
+                            });
+                        }
+                    }
+                    (b"ATTACHMENT", Some(container)) => {
+                        if let Some((content_type, ref_id, content)) = part.take() {
+                            let content =
+                                base64::engine::general_purpose::STANDARD.decode(content.trim())?;
+                            container.attachments.push(ContentContainerAttachment {
+                                content_type,
+                                ref_id,
+                                content: content.into(),
+                            });
+                        }
+                    }
+                    (b"OK_KOMM_CONTENTCONTAINER", Some(_)) => break,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        Ok(container)
+    }
+
+    pub fn attachment(&self, content_type: &str) -> Option<&ContentContainerAttachment> {
+        self.attachments
+            .iter()
+            .find(|attachment| attachment.content_type == content_type)
+    }
+}
+
 pub struct RawBase64 {
     pub body: String,
 }